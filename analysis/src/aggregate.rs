@@ -0,0 +1,184 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    coverage::{RegionCounts, Spectrum},
+    formula::{Formula, Ranked},
+};
+
+/// How much each property's ranking counts towards the aggregate. Properties
+/// that never failed carry no information about the fault and get no weight
+/// under any scheme.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Weighting {
+    /// Every failing property counts the same.
+    Equal,
+    /// Properties count in proportion to their failing executions.
+    Failures,
+    /// Properties count in inverse proportion to the number of regions their
+    /// failing executions cover: the narrower the failures, the more they say
+    /// about where the fault is.
+    Specificity,
+}
+
+impl Weighting {
+    pub const ALL: [Weighting; 3] = [Weighting::Equal, Weighting::Failures, Weighting::Specificity];
+
+    /// The weights of `spectra`, in order, summing to 1 unless no property
+    /// failed.
+    pub fn weights(&self, spectra: &[(String, Spectrum)]) -> Vec<f64> {
+        let raw = spectra
+            .iter()
+            .map(|(_, spectrum)| {
+                if spectrum.failed == 0 {
+                    return 0.0;
+                }
+                match self {
+                    Weighting::Equal => 1.0,
+                    Weighting::Failures => spectrum.failed as f64,
+                    Weighting::Specificity => {
+                        let covered = spectrum.regions.values().filter(|c| c.ef > 0).count();
+                        1.0 / covered.max(1) as f64
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        let total = raw.iter().sum::<f64>();
+        if total == 0.0 { raw } else { raw.iter().map(|w| w / total).collect() }
+    }
+}
+
+impl Display for Weighting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Weighting::Equal => write!(f, "equal"),
+            Weighting::Failures => write!(f, "failures"),
+            Weighting::Specificity => write!(f, "specificity"),
+        }
+    }
+}
+
+impl FromStr for Weighting {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "equal" => Ok(Weighting::Equal),
+            "failures" => Ok(Weighting::Failures),
+            "specificity" => Ok(Weighting::Specificity),
+            _ => Err(format!("Unknown weighting: {} (available: equal, failures, specificity)", s)),
+        }
+    }
+}
+
+/// A region of the merged ranking. The counts of `ranked` are summed over the
+/// properties; its score is the weighted sum of the per-property scores.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Aggregated {
+    #[serde(flatten)]
+    pub ranked: Ranked,
+    /// Each property's weighted score for the region. They add up to the
+    /// region's score.
+    pub contributions: BTreeMap<String, f64>,
+}
+
+/// Merges the spectra of several properties run against the same mutant into
+/// one ranking. A region a property never reached scores as uncovered for it.
+pub fn rank(
+    spectra: &[(String, Spectrum)],
+    formula: Formula,
+    weighting: Weighting,
+) -> Vec<Aggregated> {
+    let weights = weighting.weights(spectra);
+    let mut regions = BTreeMap::new();
+    for (_, spectrum) in spectra {
+        for region in spectrum.regions.keys() {
+            regions
+                .entry(region.clone())
+                .or_insert_with(|| spectrum.functions.get(region).cloned());
+        }
+    }
+    let passed = spectra.iter().map(|(_, s)| s.passed).sum::<usize>();
+    let failed = spectra.iter().map(|(_, s)| s.failed).sum::<usize>();
+
+    let mut aggregated = regions
+        .into_iter()
+        .map(|(region, function)| {
+            let mut total = RegionCounts::default();
+            let mut contributions = BTreeMap::new();
+            for ((property, spectrum), weight) in spectra.iter().zip(&weights) {
+                let counts = spectrum.regions.get(&region).copied().unwrap_or_default();
+                total.ef += counts.ef;
+                total.ep += counts.ep;
+                if *weight > 0.0 {
+                    let score = formula.score(counts, spectrum.passed, spectrum.failed);
+                    *contributions.entry(property.clone()).or_insert(0.0) += weight * score;
+                }
+            }
+            Aggregated {
+                ranked: Ranked {
+                    rank: 0,
+                    region,
+                    function,
+                    ef: total.ef,
+                    ep: total.ep,
+                    nf: failed - total.ef,
+                    np: passed - total.ep,
+                    score: contributions.values().sum(),
+                },
+                contributions,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    aggregated.sort_by(|a, b| b.ranked.score.total_cmp(&a.ranked.score));
+    for (i, a) in aggregated.iter_mut().enumerate() {
+        a.ranked.rank = i + 1;
+    }
+    aggregated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::fixtures::spectrum;
+
+    fn spectra() -> Vec<(String, Spectrum)> {
+        vec![
+            // Fails through regions 1 and 2.
+            ("InsertPost".to_string(), spectrum(2, 1, &[(1, 1, 2), (2, 1, 0)])),
+            // Fails through region 2 only, three times.
+            ("InsertModel".to_string(), spectrum(2, 3, &[(1, 0, 2), (2, 3, 0)])),
+            // Never fails.
+            ("DeleteInsert".to_string(), spectrum(4, 0, &[(3, 0, 4)])),
+        ]
+    }
+
+    #[test]
+    fn test_weights() {
+        let spectra = spectra();
+        assert_eq!(Weighting::Equal.weights(&spectra), vec![0.5, 0.5, 0.0]);
+        assert_eq!(Weighting::Failures.weights(&spectra), vec![0.25, 0.75, 0.0]);
+        let specificity = Weighting::Specificity.weights(&spectra);
+        assert!((specificity[0] - 1.0 / 3.0).abs() < 1e-9);
+        assert!((specificity[1] - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(Weighting::Equal.weights(&spectra[2..]), vec![0.0]);
+    }
+
+    #[test]
+    fn test_rank_contributions() {
+        let ranked = rank(&spectra(), Formula::Ochiai, Weighting::Equal);
+        let lines = ranked.iter().map(|a| a.ranked.region.start_line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 1, 3]);
+
+        let top = &ranked[0];
+        assert_eq!((top.ranked.ef, top.ranked.ep, top.ranked.nf), (4, 0, 0));
+        assert_eq!(top.contributions.len(), 2, "DeleteInsert has no weight");
+        assert!((top.ranked.score - 1.0).abs() < 1e-9);
+        assert!((top.contributions["InsertPost"] - 0.5).abs() < 1e-9);
+        let sum = ranked[1].contributions.values().sum::<f64>();
+        assert!((sum - ranked[1].ranked.score).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::coverage::Region;
+
+/// What makes two failing executions look like the same fault.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Clustering {
+    /// Similar sets of covered regions.
+    Coverage,
+    /// Counterexamples of the same [`shape`].
+    Shape,
+    /// Both of the above.
+    Both,
+    /// The same set of broken invariants, as `faultloc` reports them for
+    /// workloads that keep a verdict.
+    Invariant,
+}
+
+impl Display for Clustering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Clustering::Coverage => write!(f, "coverage"),
+            Clustering::Shape => write!(f, "shape"),
+            Clustering::Both => write!(f, "both"),
+            Clustering::Invariant => write!(f, "invariant"),
+        }
+    }
+}
+
+impl FromStr for Clustering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "coverage" => Ok(Clustering::Coverage),
+            "shape" => Ok(Clustering::Shape),
+            "both" => Ok(Clustering::Both),
+            "invariant" => Ok(Clustering::Invariant),
+            _ => Err(format!(
+                "Unknown clustering: {} (available: coverage, shape, both, invariant)",
+                s
+            )),
+        }
+    }
+}
+
+/// A failing execution as far as clustering is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub id: String,
+    /// The regions the failing execution covered.
+    pub signature: BTreeSet<Region>,
+    /// The [`shape`] of its counterexample, when it is known.
+    pub shape: Option<String>,
+    /// The invariants it [`broke`], when it is known.
+    pub broken: Option<BTreeSet<String>>,
+}
+
+/// The counterexample in the `Result:` line `faultloc` prints for a failed run.
+pub fn counterexample(output: &str) -> Option<&str> {
+    let line = output.lines().find(|line| line.starts_with("Result:"))?;
+    let start = line.find("arguments: [")? + "arguments: [".len();
+    let end = line.rfind(']')?;
+    (start <= end).then(|| &line[start..end])
+}
+
+/// The invariants the failure of a `faultloc` run broke, from its first
+/// `Verdict of failure` line: `black_height at L, no_red_red at root` breaks
+/// `black_height` and `no_red_red`.
+pub fn broke(output: &str) -> Option<BTreeSet<String>> {
+    let verdict = output.lines().find_map(|line| line.strip_prefix("Verdict of failure "))?;
+    let (_, verdict) = verdict.split_once(": ")?;
+    let broken = verdict
+        .split(", ")
+        .map(|broken| broken.split_once(" at ").map_or(broken, |(invariant, _)| invariant))
+        .map(str::to_string)
+        .collect();
+    Some(broken)
+}
+
+/// A counterexample with its literals blanked out, so that inputs built the
+/// same way compare equal: `(T(E, 1, 1, E), -6, 8)` becomes `(T(E, _, _, E), _, _)`.
+pub fn shape(counterexample: &str) -> String {
+    let mut shape = String::with_capacity(counterexample.len());
+    let mut chars = counterexample.chars().peekable();
+    while let Some(c) = chars.next() {
+        let negative = c == '-' && chars.peek().is_some_and(char::is_ascii_digit);
+        if c.is_alphabetic() || c == '_' {
+            // A name, which may carry digits of its own.
+            let mut word = c.to_string();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                word.push(c);
+            }
+            match word.as_str() {
+                "true" | "false" => shape.push('_'),
+                _ => shape.push_str(&word),
+            }
+        } else if c.is_ascii_digit() || negative {
+            while chars.next_if(char::is_ascii_digit).is_some() {}
+            shape.push('_');
+        } else {
+            shape.push(c);
+        }
+    }
+    shape
+}
+
+/// The Jaccard similarity of two coverage signatures.
+pub fn similarity(a: &BTreeSet<Region>, b: &BTreeSet<Region>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 { 1.0 } else { a.intersection(b).count() as f64 / union as f64 }
+}
+
+/// Groups failures by single linkage: two failures share a cluster when a
+/// chain of linked failures joins them. Failures link when their coverage is
+/// at least `threshold` similar, their shapes are equal, or both, or they
+/// broke the same invariants, depending on `by`. A failure without a shape or
+/// a verdict links to nothing by it, and by coverage alone under `Both`.
+/// Clusters hold indices into `failures` and come in the order of their first
+/// member.
+pub fn cluster(failures: &[Failure], by: Clustering, threshold: f64) -> Vec<Vec<usize>> {
+    let linked = |a: &Failure, b: &Failure| {
+        let coverage = similarity(&a.signature, &b.signature) >= threshold;
+        let shape = match (&a.shape, &b.shape) {
+            (Some(a), Some(b)) => Some(a == b),
+            _ => None,
+        };
+        match by {
+            Clustering::Coverage => coverage,
+            Clustering::Shape => shape == Some(true),
+            Clustering::Both => coverage && shape != Some(false),
+            Clustering::Invariant => a.broken.is_some() && a.broken == b.broken,
+        }
+    };
+
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent = (0..failures.len()).collect::<Vec<_>>();
+    for i in 0..failures.len() {
+        for j in i + 1..failures.len() {
+            if linked(&failures[i], &failures[j]) {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut clusters = BTreeMap::<usize, Vec<usize>>::new();
+    for i in 0..failures.len() {
+        let root = root(&mut parent, i);
+        clusters.entry(root).or_default().push(i);
+    }
+    clusters.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::fixtures::region;
+
+    fn failure(id: &str, lines: &[u32], counterexample: &str) -> Failure {
+        Failure {
+            id: id.to_string(),
+            signature: lines.iter().map(|line| region(*line)).collect(),
+            shape: Some(shape(counterexample)),
+            broken: None,
+        }
+    }
+
+    #[test]
+    fn test_counterexample_shape() {
+        let output = "Test 1 of seed 9 failed\nResult: RunResult { passed: 1, discarded: 0, status: Failed { arguments: [\"(T(E, 1, 1, E), -694, 87)\"] } }\nSeed: 9\n";
+        let found = counterexample(output).unwrap();
+        assert_eq!(found, "\"(T(E, 1, 1, E), -694, 87)\"");
+        assert_eq!(shape(found), "\"(T(E, _, _, E), _, _)\"");
+        assert_eq!(shape("(App (Var 0) (Bool true))"), "(App (Var _) (Bool _))");
+        assert_eq!(shape("T2(x_1, -3)"), "T2(x_1, _)");
+        assert_eq!(counterexample("Result: RunResult { status: Finished }"), None);
+    }
+
+    #[test]
+    fn test_cluster() {
+        let failures = vec![
+            failure("a", &[1, 2, 3], "(T(E, 1, 1, E), 4, 5)"),
+            failure("b", &[7, 8], "(E, 4, 5)"),
+            failure("c", &[1, 2, 3, 4], "(T(E, 2, 2, E), 6, 7)"),
+            failure("d", &[7, 8, 9], "(E, 4, 5)"),
+        ];
+        assert_eq!(cluster(&failures, Clustering::Coverage, 0.6), vec![vec![0, 2], vec![1, 3]]);
+        assert_eq!(
+            cluster(&failures, Clustering::Coverage, 0.9),
+            vec![vec![0], vec![1], vec![2], vec![3]]
+        );
+        assert_eq!(cluster(&failures, Clustering::Shape, 0.0), vec![vec![0, 2], vec![1, 3]]);
+        assert_eq!(cluster(&failures, Clustering::Both, 0.7), vec![vec![0, 2], vec![1], vec![3]]);
+
+        // Failures whose counterexample or verdict could not be read do not
+        // share a shape or invariants, only coverage.
+        let unparsed = vec![
+            Failure { shape: None, ..failure("e", &[1, 2], "") },
+            Failure { shape: None, ..failure("f", &[1, 2], "") },
+        ];
+        assert_eq!(cluster(&unparsed, Clustering::Shape, 0.0), vec![vec![0], vec![1]]);
+        assert_eq!(cluster(&unparsed, Clustering::Invariant, 0.0), vec![vec![0], vec![1]]);
+        assert_eq!(cluster(&unparsed, Clustering::Both, 0.9), vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_cluster_by_invariant() {
+        let outputs = [
+            "Result: RunResult { .. }\nVerdict of failure 0: black_height at R\nSeed: 1\n",
+            "Verdict of failure 0: bst at LR\nVerdict of failure 1: bst at root\n",
+            "Verdict of failure 0: black_height at root\n",
+            "Verdict of failure 0: no_red_red at L, black_height at root\n",
+        ];
+        let failures = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| Failure {
+                broken: broke(output),
+                ..failure(&i.to_string(), &[], "")
+            })
+            .collect::<Vec<_>>();
+        let names = failures[3].broken.as_ref().unwrap().iter().collect::<Vec<_>>();
+        assert_eq!(names, vec!["black_height", "no_red_red"]);
+        assert_eq!(broke("Result: RunResult { status: Finished }"), None);
+        assert_eq!(
+            cluster(&failures, Clustering::Invariant, 0.0),
+            vec![vec![0, 2], vec![1], vec![3]]
+        );
+    }
+}
@@ -0,0 +1,617 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use faultloc_mutants::marauder::{Position, Span};
+use faultloc_store::Trial;
+use serde::{Deserialize, Serialize};
+
+use crate::model::Model;
+
+/// A source span reported by `llvm-cov export`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Region {
+    pub file: String,
+    pub start_line: u32,
+    pub start_col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+}
+
+impl Region {
+    /// The region over the positions of `span`, which ends on its last
+    /// character where `llvm-cov` ends regions one column past it.
+    pub fn from_span(file: &str, span: &Span) -> Region {
+        Region {
+            file: file.to_string(),
+            start_line: span.start.line,
+            start_col: span.start.col,
+            end_line: span.end.line,
+            end_col: span.end.col + 1,
+        }
+    }
+
+    /// The positions the region covers, as an inclusive span.
+    pub fn span(&self) -> Span {
+        Span {
+            start: Position { line: self.start_line, col: self.start_col },
+            end: Position { line: self.end_line, col: self.end_col.saturating_sub(1) },
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let file = Path::new(&self.file)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_else(|| self.file.as_str().into());
+        write!(
+            f,
+            "{}:{}:{} - {}:{}",
+            file, self.start_line, self.start_col, self.end_line, self.end_col
+        )
+    }
+}
+
+/// The verdict of the property evaluation a snapshot was taken around.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    Discard,
+}
+
+impl Outcome {
+    /// Reads the outcome tag from a snapshot file stem such as
+    /// `snapshot_4242-17-fail`, where the tag is the last `-` separated part.
+    pub fn from_stem(stem: &str) -> Option<Outcome> {
+        match stem.rsplit('-').next()? {
+            "pass" => Some(Outcome::Pass),
+            "fail" => Some(Outcome::Fail),
+            "discard" => Some(Outcome::Discard),
+            _ => None,
+        }
+    }
+}
+
+/// The region counters of a single exported snapshot.
+#[derive(Debug, Clone)]
+pub struct Execution {
+    pub id: String,
+    pub outcome: Outcome,
+    pub counts: BTreeMap<Region, u64>,
+    pub functions: BTreeMap<Region, String>,
+    /// The size of the input, when the harness recorded it.
+    pub size: Option<usize>,
+}
+
+// The subset of the `llvm-cov export --format=text` schema we rely on.
+#[derive(Deserialize)]
+struct Export {
+    data: Vec<ExportData>,
+}
+
+#[derive(Deserialize)]
+struct ExportData {
+    functions: Vec<ExportFunction>,
+}
+
+#[derive(Deserialize)]
+struct ExportFunction {
+    name: String,
+    regions: Vec<Vec<u64>>,
+    filenames: Vec<String>,
+}
+
+// Region kinds as numbered by llvm-cov; only code regions carry execution counts
+// that are meaningful for localization.
+const CODE_REGION: u64 = 0;
+
+/// `name` demangled without its hash, as `rustfilt` prints it, so that
+/// function filters match whether or not `llvm-cov export` was given a
+/// demangler. Names that are not mangled stay as they are.
+pub fn demangle(name: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(name))
+}
+
+impl Execution {
+    /// Parses a coverage export. Instantiations of the same generic
+    /// function share source spans, so their counters are summed.
+    pub fn from_export(id: String, outcome: Outcome, json: &str) -> Result<Execution, String> {
+        let export: Export =
+            serde_json::from_str(json).map_err(|e| format!("invalid coverage export: {}", e))?;
+
+        let mut counts = BTreeMap::new();
+        let mut functions = BTreeMap::new();
+        for data in export.data {
+            for function in data.functions {
+                for region in function.regions {
+                    let [start_line, start_col, end_line, end_col, count, file_id, _, kind] =
+                        region[..]
+                    else {
+                        return Err(format!("malformed region in {}", function.name));
+                    };
+                    if kind != CODE_REGION {
+                        continue;
+                    }
+                    let Some(file) = function.filenames.get(file_id as usize) else {
+                        return Err(format!("unknown file id {} in {}", file_id, function.name));
+                    };
+                    let region = Region {
+                        file: file.clone(),
+                        start_line: start_line as u32,
+                        start_col: start_col as u32,
+                        end_line: end_line as u32,
+                        end_col: end_col as u32,
+                    };
+                    *counts.entry(region.clone()).or_insert(0) += count;
+                    functions.entry(region).or_insert_with(|| demangle(&function.name));
+                }
+            }
+        }
+
+        Ok(Execution { id, outcome, counts, functions, size: None })
+    }
+
+    pub fn covers(&self, region: &Region) -> bool {
+        self.counts.get(region).is_some_and(|count| *count > 0)
+    }
+}
+
+/// The input sizes the harness recorded next to the snapshots, from every
+/// `sizes_*.txt` under `dir`, by snapshot name.
+fn load_sizes(dir: &Path) -> Result<BTreeMap<String, usize>, String> {
+    let mut sizes = BTreeMap::new();
+    let paths = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "txt")
+                && path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("sizes_"))
+        });
+    for path in paths {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let size =
+                line.split_once(' ').and_then(|(name, size)| Some((name, size.parse().ok()?)));
+            let Some((name, size)) = size else {
+                return Err(format!("{}: malformed line: {}", path.display(), line));
+            };
+            sizes.insert(name.to_string(), size);
+        }
+    }
+    Ok(sizes)
+}
+
+/// Loads every `snapshot_*.json` under `dir` whose name carries an outcome tag.
+pub fn load_dir(dir: &Path) -> Result<Vec<Execution>, String> {
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "json")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("snapshot_"))
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    let sizes = load_sizes(dir)?;
+    let mut executions = Vec::with_capacity(paths.len());
+    for path in paths {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let Some(outcome) = Outcome::from_stem(&stem) else {
+            eprintln!("Skipping {}: no pass/fail/discard tag in its name", path.display());
+            continue;
+        };
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let mut execution = Execution::from_export(stem, outcome, &json)?;
+        execution.size = sizes.get(&execution.id).copied();
+        executions.push(execution);
+    }
+    Ok(executions)
+}
+
+/// Loads every `contrast_<pid>-<group>-<member>-<outcome>.json` under `dir` as
+/// near-miss pairs: the failing member 0 of each group with each passing
+/// neighbor of the same group.
+pub fn load_contrast_dir(dir: &Path) -> Result<Vec<(Execution, Execution)>, String> {
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "json")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("contrast_"))
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    let sizes = load_sizes(dir)?;
+    let mut anchors = BTreeMap::new();
+    let mut neighbors = BTreeMap::<(String, String), Vec<Execution>>::new();
+    for path in paths {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let parts = stem.trim_start_matches("contrast_").split('-').collect::<Vec<_>>();
+        let (Some(outcome), [pid, group, member, _]) = (Outcome::from_stem(&stem), &parts[..])
+        else {
+            eprintln!(
+                "Skipping {}: not named contrast_<pid>-<group>-<member>-<outcome>",
+                path.display()
+            );
+            continue;
+        };
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let mut execution = Execution::from_export(stem.clone(), outcome, &json)?;
+        execution.size = sizes.get(&stem).copied();
+        let group = (pid.to_string(), group.to_string());
+        match (*member, outcome) {
+            ("0", Outcome::Fail) => {
+                anchors.insert(group, execution);
+            },
+            (_, Outcome::Pass) => neighbors.entry(group).or_default().push(execution),
+            _ => eprintln!("Skipping {}: neither a failing anchor nor a passing neighbor", stem),
+        }
+    }
+
+    let mut pairs = vec![];
+    for (group, neighbors) in neighbors {
+        let Some(anchor) = anchors.get(&group) else {
+            eprintln!("Skipping group {}-{}: no failing anchor", group.0, group.1);
+            continue;
+        };
+        pairs.extend(neighbors.into_iter().map(|neighbor| (anchor.clone(), neighbor)));
+    }
+    Ok(pairs)
+}
+
+/// How many passing and failing executions cover a region.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct RegionCounts {
+    pub ef: usize,
+    pub ep: usize,
+}
+
+/// The summed weights of the failing and passing executions of a region
+/// under a count [`Model`]. Each execution weighs at most 1, so they stay
+/// comparable to [`RegionCounts`].
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq)]
+pub struct Weights {
+    pub ef: f64,
+    pub ep: f64,
+}
+
+/// Pass/fail coverage counts per region over a set of executions. Discarded
+/// executions are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Spectrum {
+    pub passed: usize,
+    pub failed: usize,
+    pub regions: BTreeMap<Region, RegionCounts>,
+    pub functions: BTreeMap<Region, String>,
+    /// The model the spectrum was [`weigh`](Spectrum::weigh)ed under.
+    pub model: Model,
+    /// Per-region weights under a count model; empty under binary coverage,
+    /// where `regions` says it all.
+    pub weights: BTreeMap<Region, Weights>,
+}
+
+impl Spectrum {
+    pub fn from_executions(executions: &[Execution]) -> Spectrum {
+        let mut spectrum = Spectrum::default();
+        for execution in executions {
+            spectrum.add(execution);
+        }
+        spectrum
+    }
+
+    /// The spectrum a stored trial was recorded with.
+    pub fn from_trial(trial: &Trial) -> Spectrum {
+        let mut spectrum = Spectrum {
+            passed: trial.tests.passed,
+            failed: trial.tests.failed,
+            ..Default::default()
+        };
+        for record in &trial.regions {
+            let region = Region {
+                file: record.file.clone(),
+                start_line: record.start_line,
+                start_col: record.start_col,
+                end_line: record.end_line,
+                end_col: record.end_col,
+            };
+            spectrum.regions.insert(region.clone(), RegionCounts { ef: record.ef, ep: record.ep });
+            if let Some(function) = &record.function {
+                spectrum.functions.insert(region, demangle(function));
+            }
+        }
+        spectrum
+    }
+
+    /// The contrastive spectrum of near-miss pairs: each pair counts as one
+    /// failing and one passing execution, so a region covered by both sides
+    /// of every pair scores no higher than one covered by neither.
+    pub fn from_pairs(pairs: &[(Execution, Execution)]) -> Spectrum {
+        let mut spectrum = Spectrum::default();
+        for (failing, passing) in pairs {
+            spectrum.add(failing);
+            spectrum.add(passing);
+        }
+        spectrum
+    }
+
+    /// Adds the executions of `other`, as if both had been collected together.
+    pub fn merge(&mut self, other: &Spectrum) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        for (region, counts) in &other.regions {
+            let merged = self.regions.entry(region.clone()).or_default();
+            merged.ef += counts.ef;
+            merged.ep += counts.ep;
+        }
+        for (region, function) in &other.functions {
+            self.functions.entry(region.clone()).or_insert_with(|| function.clone());
+        }
+        // Both sides are expected to be under the same model.
+        for (region, weights) in &other.weights {
+            let merged = self.weights.entry(region.clone()).or_default();
+            merged.ef += weights.ef;
+            merged.ep += weights.ep;
+        }
+    }
+
+    /// The passing side of the spectrum alone.
+    pub fn passing(&self) -> Spectrum {
+        let mut passing = self.clone();
+        passing.failed = 0;
+        for counts in passing.regions.values_mut() {
+            counts.ef = 0;
+        }
+        for weights in passing.weights.values_mut() {
+            weights.ef = 0.0;
+        }
+        passing
+    }
+
+    /// The failing side of the spectrum alone.
+    pub fn failing(&self) -> Spectrum {
+        let mut failing = self.clone();
+        failing.passed = 0;
+        for counts in failing.regions.values_mut() {
+            counts.ep = 0;
+        }
+        for weights in failing.weights.values_mut() {
+            weights.ep = 0.0;
+        }
+        failing
+    }
+
+    /// Weighs the executions the spectrum was built from under `model`. A
+    /// region's weight in an execution is scaled by its largest weight over
+    /// all of them, so that the busiest execution counts as 1 and the
+    /// formulas can read the weights as execution counts.
+    pub fn weigh<'a>(&mut self, executions: impl IntoIterator<Item = &'a Execution>, model: Model) {
+        self.model = model;
+        self.weights.clear();
+        if model == Model::Binary {
+            return;
+        }
+
+        let executions = executions
+            .into_iter()
+            .filter(|execution| execution.outcome != Outcome::Discard)
+            .collect::<Vec<_>>();
+        let mut largest = BTreeMap::<&Region, f64>::new();
+        for execution in &executions {
+            for (region, count) in &execution.counts {
+                let weight = model.weight(*count, execution.size);
+                let largest = largest.entry(region).or_default();
+                *largest = largest.max(weight);
+            }
+        }
+        for region in self.regions.keys() {
+            self.weights.insert(region.clone(), Weights::default());
+        }
+        for execution in &executions {
+            for (region, count) in &execution.counts {
+                let largest = largest[region];
+                if largest == 0.0 {
+                    continue;
+                }
+                let weight = model.weight(*count, execution.size) / largest;
+                let weights = self.weights.entry(region.clone()).or_default();
+                match execution.outcome {
+                    Outcome::Pass => weights.ep += weight,
+                    Outcome::Fail => weights.ef += weight,
+                    Outcome::Discard => unreachable!(),
+                }
+            }
+        }
+    }
+
+    pub fn add(&mut self, execution: &Execution) {
+        match execution.outcome {
+            Outcome::Pass => self.passed += 1,
+            Outcome::Fail => self.failed += 1,
+            Outcome::Discard => return,
+        }
+        for (region, count) in &execution.counts {
+            let counts = self.regions.entry(region.clone()).or_default();
+            if *count > 0 {
+                match execution.outcome {
+                    Outcome::Pass => counts.ep += 1,
+                    Outcome::Fail => counts.ef += 1,
+                    Outcome::Discard => unreachable!(),
+                }
+            }
+        }
+        for (region, function) in &execution.functions {
+            self.functions.entry(region.clone()).or_insert_with(|| function.clone());
+        }
+    }
+}
+
+/// Spectra for the tests of the modules that rank and compare them.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+
+    /// Line `line` of `implementation.rs`, as a region.
+    pub fn region(line: u32) -> Region {
+        Region {
+            file: "implementation.rs".to_string(),
+            start_line: line,
+            start_col: 1,
+            end_line: line,
+            end_col: 10,
+        }
+    }
+
+    /// `passed` and `failed` inputs over the regions of the `(line, ef, ep)`
+    /// triples.
+    pub fn spectrum(passed: usize, failed: usize, regions: &[(u32, usize, usize)]) -> Spectrum {
+        let mut spectrum = Spectrum { passed, failed, ..Default::default() };
+        for &(line, ef, ep) in regions {
+            spectrum.regions.insert(region(line), RegionCounts { ef, ep });
+        }
+        spectrum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "data": [{
+            "files": [],
+            "functions": [
+                {
+                    "name": "bst::implementation::insert",
+                    "count": 3,
+                    "regions": [[24, 53, 60, 2, 3, 0, 0, 0], [30, 13, 30, 20, 0, 0, 0, 0], [31, 1, 31, 9, 2, 0, 0, 3]],
+                    "filenames": ["/w/BST/src/implementation.rs"]
+                },
+                {
+                    "name": "bst::implementation::insert",
+                    "count": 1,
+                    "regions": [[30, 13, 30, 20, 1, 0, 0, 0]],
+                    "filenames": ["/w/BST/src/implementation.rs"]
+                }
+            ],
+            "totals": {}
+        }],
+        "type": "llvm.coverage.json.export",
+        "version": "2.0.1"
+    }"#;
+
+    fn region(start_line: u32, start_col: u32, end_line: u32, end_col: u32) -> Region {
+        Region {
+            file: "/w/BST/src/implementation.rs".to_string(),
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        }
+    }
+
+    #[test]
+    fn test_outcome_from_stem() {
+        assert_eq!(Outcome::from_stem("snapshot_4242-17-fail"), Some(Outcome::Fail));
+        assert_eq!(Outcome::from_stem("snapshot_4242-17-pass"), Some(Outcome::Pass));
+        assert_eq!(Outcome::from_stem("snapshot_4242-17-discard"), Some(Outcome::Discard));
+        assert_eq!(Outcome::from_stem("snapshot_4242-1234567890"), None);
+    }
+
+    #[test]
+    fn test_from_export_sums_instantiations() {
+        let execution = Execution::from_export("e".to_string(), Outcome::Pass, EXPORT).unwrap();
+        assert_eq!(execution.counts.len(), 2, "gap regions are skipped");
+        assert_eq!(execution.counts[&region(24, 53, 60, 2)], 3);
+        assert_eq!(execution.counts[&region(30, 13, 30, 20)], 1);
+        assert!(execution.covers(&region(30, 13, 30, 20)));
+    }
+
+    #[test]
+    fn test_load_contrast_dir() {
+        let dir = std::env::temp_dir().join(format!("faultloc-contrast-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "contrast_1-0-0-fail",
+            "contrast_1-0-1-pass",
+            "contrast_1-0-2-pass",
+            "contrast_1-1-1-pass",
+            "snapshot_1-0-pass",
+        ] {
+            std::fs::write(dir.join(format!("{}.json", name)), EXPORT).unwrap();
+        }
+        std::fs::write(dir.join("sizes_1.txt"), "contrast_1-0-0-fail 7\n").unwrap();
+        let pairs = load_contrast_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let ids = pairs.iter().map(|(f, p)| (f.id.as_str(), p.id.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                ("contrast_1-0-0-fail", "contrast_1-0-1-pass"),
+                ("contrast_1-0-0-fail", "contrast_1-0-2-pass"),
+            ],
+            "group 1 has no failing anchor"
+        );
+        assert_eq!((pairs[0].0.size, pairs[0].1.size), (Some(7), None));
+        let spectrum = Spectrum::from_pairs(&pairs);
+        assert_eq!((spectrum.passed, spectrum.failed), (2, 2));
+    }
+
+    #[test]
+    fn test_spectrum_counts() {
+        let pass = Execution::from_export("p".to_string(), Outcome::Pass, EXPORT).unwrap();
+        let mut fail = pass.clone();
+        fail.outcome = Outcome::Fail;
+        fail.counts.insert(region(30, 13, 30, 20), 0);
+        let mut discard = pass.clone();
+        discard.outcome = Outcome::Discard;
+
+        let spectrum = Spectrum::from_executions(&[pass, fail, discard]);
+        assert_eq!((spectrum.passed, spectrum.failed), (1, 1));
+        assert_eq!(spectrum.regions[&region(24, 53, 60, 2)], RegionCounts { ef: 1, ep: 1 });
+        assert_eq!(spectrum.regions[&region(30, 13, 30, 20)], RegionCounts { ef: 0, ep: 1 });
+    }
+
+    #[test]
+    fn test_spectrum_weights() {
+        let mut fail = Execution::from_export("f".to_string(), Outcome::Fail, EXPORT).unwrap();
+        fail.counts.insert(region(24, 53, 60, 2), 10);
+        fail.size = Some(10);
+        let mut pass = fail.clone();
+        pass.outcome = Outcome::Pass;
+        pass.counts.insert(region(24, 53, 60, 2), 1);
+        pass.size = Some(1);
+        let executions = [fail, pass];
+
+        let weights = |model: Model| {
+            let mut spectrum = Spectrum::from_executions(&executions);
+            spectrum.weigh(&executions, model);
+            assert_eq!(spectrum.model, model);
+            spectrum.weights.get(&region(24, 53, 60, 2)).copied()
+        };
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert_eq!(weights(Model::Binary), None);
+        assert_eq!(weights(Model::Counts), Some(Weights { ef: 1.0, ep: 0.1 }));
+        assert_eq!(weights(Model::Size), Some(Weights { ef: 1.0, ep: 1.0 }));
+        let log = weights(Model::Log).unwrap();
+        assert!(close(log.ef, 1.0) && close(log.ep, 2f64.ln() / 11f64.ln()), "{:?}", log);
+        for model in Model::ALL {
+            assert_eq!(model.to_string().parse::<Model>(), Ok(model));
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! How failing model-based properties diverged from their model, as
+//! `faultloc` saves it next to the coverage snapshots of each recorded
+//! failure.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// One step of the diff from the list a model expected to the one the
+/// implementation produced.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Edit {
+    Missing { key: i32, value: i32 },
+    Unexpected { key: i32, value: i32 },
+    Changed { key: i32, expected: i32, actual: i32 },
+}
+
+/// How a failing model-based property diverged from its model, as `faultloc`
+/// saves it for each recorded failure.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The first key the lists disagree on.
+    pub first: Option<i32>,
+    /// Every key the lists disagree on.
+    pub keys: Vec<i32>,
+    pub edits: Vec<Edit>,
+    pub expected: Vec<(i32, i32)>,
+    pub actual: Vec<(i32, i32)>,
+}
+
+/// Loads every `divergence_<pid>-<group>.json` under `dir`, by `<pid>-<group>`,
+/// the name of the failure among the contrastive snapshots.
+pub fn load_dir(dir: &Path) -> Result<BTreeMap<String, Divergence>, String> {
+    let mut divergences = BTreeMap::new();
+    for entry in
+        std::fs::read_dir(dir).map_err(|e| format!("failed to read {}: {}", dir.display(), e))?
+    {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let Some(failure) = stem.strip_prefix("divergence_") else {
+            continue;
+        };
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let divergence = serde_json::from_str(&json)
+            .map_err(|e| format!("{}: invalid divergence: {}", path.display(), e))?;
+        divergences.insert(failure.to_string(), divergence);
+    }
+    Ok(divergences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("faultloc-divergence-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let divergence = r#"{"first": 3, "keys": [3, 5], "edits": [
+            {"op": "missing", "key": 3, "value": 4},
+            {"op": "changed", "key": 5, "expected": 1, "actual": 2}
+        ], "expected": [[3, 4], [5, 1]], "actual": [[5, 2]]}"#;
+        std::fs::write(dir.join("divergence_1-0.json"), divergence).unwrap();
+        std::fs::write(dir.join("sizes_1.txt"), "contrast_1-0-0-fail 7\n").unwrap();
+        let divergences = load_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(divergences.keys().collect::<Vec<_>>(), vec!["1-0"]);
+        let divergence = &divergences["1-0"];
+        assert_eq!((divergence.first, &divergence.keys[..]), (Some(3), &[3, 5][..]));
+        assert_eq!(divergence.edits[1], Edit::Changed { key: 5, expected: 1, actual: 2 });
+    }
+}
@@ -0,0 +1,254 @@
+//! Rankings and spectra in formats editors and review tools read: SARIF for
+//! suspicious regions, and LCOV for the coverage of either side of a
+//! spectrum.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Component, Path},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    coverage::{Region, Spectrum},
+    formula::{Formula, Ranked},
+};
+
+/// The SARIF rule every suspicious region is reported under.
+pub const RULE: &str = "suspicious-region";
+
+/// The SARIF level of a region ranked `rank`: the most suspicious region is
+/// an error, the next few are warnings, and the rest notes.
+pub fn level(rank: usize) -> &'static str {
+    match rank {
+        1 => "error",
+        2..=5 => "warning",
+        _ => "note",
+    }
+}
+
+/// `file` as a SARIF artifact URI: relative to `root` when it lies under it,
+/// and a `file://` URI when it is absolute otherwise.
+fn uri(file: &str, root: Option<&Path>) -> String {
+    let path = Path::new(file);
+    if let Some(relative) = root.and_then(|root| path.strip_prefix(root).ok()) {
+        return relative.to_string_lossy().replace('\\', "/");
+    }
+    if path.is_absolute() {
+        let parts = path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        format!("file:///{}", parts.join("/"))
+    } else {
+        file.replace('\\', "/")
+    }
+}
+
+/// A SARIF 2.1.0 log with a result per region of `ranked` that scored above
+/// zero. `ranked` is expected in rank order, as the formulas return it.
+pub fn sarif(ranked: &[Ranked], formula: Formula, root: Option<&Path>) -> Value {
+    let total = ranked.len();
+    let results = ranked
+        .iter()
+        .filter(|r| r.score > 0.0)
+        .map(|r| {
+            let passed = r.ep + r.np;
+            let failed = r.ef + r.nf;
+            let function = r.function.as_deref().map(|f| format!(" in {}", f)).unwrap_or_default();
+            json!({
+                "ruleId": RULE,
+                "level": level(r.rank),
+                // SARIF ranks results from 0 to 100, most relevant highest.
+                "rank": 100.0 * (total - r.rank + 1) as f64 / total as f64,
+                "message": {
+                    "text": format!(
+                        "Suspicious region{}, rank {} of {} by {} (score {:.4}): reached by {} of {} failing and {} of {} passing inputs",
+                        function, r.rank, total, formula, r.score, r.ef, failed, r.ep, passed
+                    ),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri(&r.region.file, root) },
+                        "region": {
+                            "startLine": r.region.start_line,
+                            "startColumn": r.region.start_col,
+                            "endLine": r.region.end_line,
+                            "endColumn": r.region.end_col,
+                        },
+                    },
+                }],
+                "properties": {
+                    "rank": r.rank,
+                    "formula": formula,
+                    "score": r.score,
+                    "function": r.function,
+                    "ef": r.ef,
+                    "ep": r.ep,
+                    "nf": r.nf,
+                    "np": r.np,
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "faultloc",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": RULE,
+                        "shortDescription": { "text": "Suspicious region" },
+                        "fullDescription": {
+                            "text": "A region the failing inputs of a property-based test reach more than the passing ones, ranked by a spectrum-based formula."
+                        },
+                    }],
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// The spectrum as an LCOV tracefile, with the inputs that reached each line
+/// as its hit count. Take [`Spectrum::passing`] or [`Spectrum::failing`] for
+/// the coverage of one side alone.
+///
+/// A line counts what the regions starting on it count at most; a line no
+/// region starts on counts what the innermost region spanning it does.
+pub fn lcov(spectrum: &Spectrum) -> String {
+    let mut files = BTreeMap::<&str, Vec<(&Region, usize)>>::new();
+    for (region, counts) in &spectrum.regions {
+        files.entry(&region.file).or_default().push((region, counts.ef + counts.ep));
+    }
+
+    let mut text = String::new();
+    for (file, regions) in files {
+        writeln!(text, "TN:\nSF:{}", file).unwrap();
+
+        // A function starts where its first region does and counts what its
+        // busiest region does.
+        let mut functions = BTreeMap::<&str, (u32, usize)>::new();
+        for (region, hits) in &regions {
+            if let Some(name) = spectrum.functions.get(*region) {
+                let function = functions.entry(name).or_insert((region.start_line, 0));
+                function.0 = function.0.min(region.start_line);
+                function.1 = function.1.max(*hits);
+            }
+        }
+        let mut starts = functions.iter().collect::<Vec<_>>();
+        starts.sort_by_key(|(name, (line, _))| (*line, *name));
+        for (name, (line, _)) in &starts {
+            writeln!(text, "FN:{},{}", line, name).unwrap();
+        }
+        for (name, (_, hits)) in &starts {
+            writeln!(text, "FNDA:{},{}", hits, name).unwrap();
+        }
+        let hit = starts.iter().filter(|(_, (_, hits))| *hits > 0).count();
+        writeln!(text, "FNF:{}\nFNH:{}", starts.len(), hit).unwrap();
+
+        let mut lines = BTreeMap::<u32, usize>::new();
+        for (region, hits) in &regions {
+            let line = lines.entry(region.start_line).or_default();
+            *line = (*line).max(*hits);
+        }
+        for (region, _) in &regions {
+            for line in region.start_line + 1..=region.end_line {
+                lines.entry(line).or_insert_with(|| {
+                    regions
+                        .iter()
+                        .filter(|(r, _)| r.start_line < line && line <= r.end_line)
+                        .max_by_key(|(r, _)| {
+                            (
+                                (r.start_line, r.start_col),
+                                std::cmp::Reverse((r.end_line, r.end_col)),
+                            )
+                        })
+                        .map(|(_, hits)| *hits)
+                        .unwrap_or_default()
+                });
+            }
+        }
+        for (line, hits) in &lines {
+            writeln!(text, "DA:{},{}", line, hits).unwrap();
+        }
+        let hit = lines.values().filter(|hits| **hits > 0).count();
+        writeln!(text, "LF:{}\nLH:{}\nend_of_record", lines.len(), hit).unwrap();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coverage::RegionCounts, formula};
+
+    fn region(file: &str, start_line: u32, start_col: u32, end_line: u32, end_col: u32) -> Region {
+        Region { file: file.to_string(), start_line, start_col, end_line, end_col }
+    }
+
+    fn spectrum() -> Spectrum {
+        let file = "/home/w/BST/src/implementation.rs";
+        let mut spectrum = Spectrum { passed: 3, failed: 1, ..Default::default() };
+        for (region, ef, ep) in [
+            (region(file, 1, 35, 6, 2), 1, 3),
+            (region(file, 2, 15, 4, 6), 1, 0),
+            (region(file, 4, 12, 5, 6), 0, 3),
+            (region("/home/w/BST/src/spec.rs", 1, 1, 1, 20), 0, 0),
+        ] {
+            spectrum.functions.insert(region.clone(), "insert".to_string());
+            spectrum.regions.insert(region, RegionCounts { ef, ep });
+        }
+        spectrum.functions.insert(region("/home/w/BST/src/spec.rs", 1, 1, 1, 20), "prop".into());
+        spectrum
+    }
+
+    #[test]
+    fn test_sarif() {
+        let ranked = formula::rank(&spectrum(), Formula::Ochiai);
+        let log = sarif(&ranked, Formula::Ochiai, Some(Path::new("/home/w/BST")));
+        assert_eq!(log["version"], "2.1.0");
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        // The regions no failing input reached score zero and are left out.
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[0]["rank"], 100.0);
+        assert_eq!(results[0]["properties"]["score"], 1.0);
+        assert_eq!(results[0]["properties"]["ef"], 1);
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/implementation.rs");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["endColumn"], 6);
+
+        assert_eq!(uri("/home/w/BST/src/spec.rs", None), "file:///home/w/BST/src/spec.rs");
+        assert_eq!(uri("src/spec.rs", Some(Path::new("/elsewhere"))), "src/spec.rs");
+        assert_eq!(level(6), "note");
+    }
+
+    #[test]
+    fn test_lcov() {
+        let spectrum = spectrum();
+        let failing = lcov(&spectrum.failing());
+        let records = failing.split("end_of_record\n").collect::<Vec<_>>();
+        assert_eq!(records.len(), 3, "one record per file and the empty rest");
+        assert!(records[0].starts_with("TN:\nSF:/home/w/BST/src/implementation.rs\n"));
+        assert!(records[0].contains("FN:1,insert\nFNDA:1,insert\nFNF:1\nFNH:1\n"));
+        // Line 3 only lies in the failing branch; line 4 starts the passing
+        // one, and line 6 closes the function.
+        let lines = "DA:1,1\nDA:2,1\nDA:3,1\nDA:4,0\nDA:5,0\nDA:6,1\nLF:6\nLH:4\n";
+        assert!(records[0].ends_with(lines), "{}", records[0]);
+        assert!(records[1].contains("DA:1,0\nLF:1\nLH:0\n"));
+
+        let passing = lcov(&spectrum.passing());
+        assert!(passing.contains("FNDA:3,insert\n"));
+        assert!(passing.contains("DA:2,0\nDA:3,0\nDA:4,3\nDA:5,3\nDA:6,3\n"));
+    }
+}
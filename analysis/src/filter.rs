@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::coverage::{Region, Spectrum, demangle};
+
+/// Which regions are candidates for blame, as the `[faultloc]` section of a
+/// workload's `marauder.toml` or of the top-level `config.toml` sets them:
+///
+/// ```toml
+/// [faultloc]
+/// include = ["src/implementation.rs"]
+/// exclude = ["src/spec.rs", "src/strategies/*"]
+/// exclude_functions = ["*::spec::*"]
+/// ```
+///
+/// Path patterns match a region's file or any of its trailing components,
+/// and function patterns its demangled function name; `*` matches any run of
+/// characters. A region is a candidate if it matches some include pattern
+/// (or there are none) and no exclude pattern.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Filter {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub include_functions: Vec<String>,
+    #[serde(default)]
+    pub exclude_functions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Config {
+    faultloc: Option<Filter>,
+}
+
+impl Filter {
+    /// The `[faultloc]` section of a TOML file; a file without one filters
+    /// nothing.
+    pub fn from_toml(toml: &str) -> Result<Filter, String> {
+        let config: Config = toml::from_str(toml).map_err(|e| e.to_string())?;
+        Ok(config.faultloc.unwrap_or_default())
+    }
+
+    pub fn from_file(path: &Path) -> Result<Filter, String> {
+        let toml = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Filter::from_toml(&toml).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Adds the patterns of `other`, so that a workload's filter refines the
+    /// top-level one.
+    pub fn extend(&mut self, other: Filter) {
+        self.include.extend(other.include);
+        self.exclude.extend(other.exclude);
+        self.include_functions.extend(other.include_functions);
+        self.exclude_functions.extend(other.exclude_functions);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Filter::default()
+    }
+
+    /// Whether `region` is left in by the filter. Function patterns match the
+    /// demangled name, so mangled names from older exports still match.
+    pub fn candidate(&self, region: &Region, function: Option<&str>) -> bool {
+        let path = |pattern: &String| matches_path(pattern, &region.file);
+        let function = function.map(demangle);
+        let function = |pattern: &String| function.as_ref().is_some_and(|f| matches(pattern, f));
+        (self.include.is_empty() || self.include.iter().any(path))
+            && (self.include_functions.is_empty() || self.include_functions.iter().any(function))
+            && !self.exclude.iter().any(path)
+            && !self.exclude_functions.iter().any(function)
+    }
+
+    /// Splits `spectrum` into its candidate regions and the excluded rest.
+    /// Both keep the execution totals, so the excluded side still tells how
+    /// often failing and passing executions went through it.
+    pub fn split(&self, spectrum: &Spectrum) -> (Spectrum, Spectrum) {
+        let mut candidates =
+            Spectrum { passed: spectrum.passed, failed: spectrum.failed, ..Default::default() };
+        candidates.model = spectrum.model;
+        let mut excluded = candidates.clone();
+        for (region, counts) in &spectrum.regions {
+            let function = spectrum.functions.get(region);
+            let side = if self.candidate(region, function.map(String::as_str)) {
+                &mut candidates
+            } else {
+                &mut excluded
+            };
+            side.regions.insert(region.clone(), *counts);
+            if let Some(function) = function {
+                side.functions.insert(region.clone(), function.clone());
+            }
+            if let Some(weights) = spectrum.weights.get(region) {
+                side.weights.insert(region.clone(), *weights);
+            }
+        }
+        (candidates, excluded)
+    }
+}
+
+/// Whether `pattern` matches `file` or one of its trailing components.
+fn matches_path(pattern: &str, file: &str) -> bool {
+    let file = file.replace('\\', "/");
+    matches(pattern, &file)
+        || file.match_indices('/').any(|(i, _)| matches(pattern, &file[i + 1..]))
+}
+
+/// Whether `pattern` matches all of `text`, with `*` matching any run of
+/// characters.
+fn matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else { return false };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else { return rest.is_empty() };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::RegionCounts;
+
+    fn region(file: &str) -> Region {
+        Region { file: file.to_string(), start_line: 1, start_col: 1, end_line: 1, end_col: 10 }
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*::spec::*", "bst::spec::is_bst"));
+        assert!(!matches("*::spec::*", "bst::implementation::insert"));
+        assert!(matches("a*b*c", "abbc"));
+        assert!(!matches("a*bc", "abc*"));
+        assert!(matches_path("src/strategies/*", "/w/BST/src/strategies/bespoke_crabcheck.rs"));
+        assert!(matches_path("spec.rs", "/w/BST/src/spec.rs"));
+        assert!(!matches_path("spec.rs", "/w/BST/src/inspec.rs"));
+    }
+
+    #[test]
+    fn test_split() {
+        let filter = Filter::from_toml(
+            r#"
+            languages = ["Rust"]
+
+            [faultloc]
+            exclude = ["src/parser.rs"]
+            exclude_functions = ["*::spec::*"]
+            "#,
+        )
+        .unwrap();
+        assert!(Filter::from_toml("ignore = []").unwrap().is_empty());
+        assert!(Filter::from_toml("[faultloc]\nexclude = \"spec.rs\"").is_err());
+
+        let mut spectrum = Spectrum { passed: 3, failed: 1, ..Default::default() };
+        for file in ["src/implementation.rs", "src/parser.rs", "src/spec.rs"] {
+            spectrum.regions.insert(region(file), RegionCounts { ef: 1, ep: 2 });
+        }
+        spectrum.functions.insert(region("src/spec.rs"), "stlc::spec::to_list".to_string());
+
+        let (candidates, excluded) = filter.split(&spectrum);
+        let files = |s: &Spectrum| s.regions.keys().map(|r| r.file.clone()).collect::<Vec<_>>();
+        assert_eq!(files(&candidates), vec!["src/implementation.rs"]);
+        assert_eq!(files(&excluded), vec!["src/parser.rs", "src/spec.rs"]);
+        assert_eq!((excluded.passed, excluded.failed), (3, 1));
+        assert_eq!(excluded.functions.len(), 1);
+
+        let mut only =
+            Filter { include: vec!["implementation.rs".to_string()], ..Default::default() };
+        only.extend(filter);
+        assert!(only.candidate(&region("src/implementation.rs"), None));
+        assert!(!only.candidate(&region("src/lib.rs"), None));
+    }
+
+    #[test]
+    fn test_mangled_function() {
+        let filter = Filter {
+            exclude_functions: vec!["*::spec::*".to_string()],
+            include_functions: vec!["bst::*".to_string()],
+            ..Default::default()
+        };
+        let v0 = "_RNvNtCsjg8EO5Wbbkc_3bst4spec7to_list";
+        let legacy = "_ZN3bst14implementation6insert17h0123456789abcdefE";
+        assert_eq!(demangle(v0), "bst::spec::to_list");
+        assert_eq!(demangle(legacy), "bst::implementation::insert");
+        assert_eq!(demangle("bst::spec::to_list"), "bst::spec::to_list");
+        assert!(!filter.candidate(&region("src/spec.rs"), Some(v0)));
+        assert!(filter.candidate(&region("src/implementation.rs"), Some(legacy)));
+    }
+}
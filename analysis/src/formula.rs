@@ -0,0 +1,185 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::coverage::{Region, RegionCounts, Spectrum};
+
+/// Spectrum-based suspiciousness formulas. Each one scores a region from
+/// `ef`/`ep` (failing/passing executions covering it) and `nf`/`np`
+/// (failing/passing executions not covering it).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Formula {
+    Ochiai,
+    Tarantula,
+    DStar,
+    Op2,
+    Jaccard,
+}
+
+impl Formula {
+    pub const ALL: [Formula; 5] =
+        [Formula::Ochiai, Formula::Tarantula, Formula::DStar, Formula::Op2, Formula::Jaccard];
+
+    pub fn score(&self, counts: RegionCounts, passed: usize, failed: usize) -> f64 {
+        self.weighted(counts.ef as f64, counts.ep as f64, passed, failed)
+    }
+
+    /// [`Formula::score`] of the summed weights of a count model, which
+    /// stand in for `ef` and `ep`.
+    pub fn weighted(&self, ef: f64, ep: f64, passed: usize, failed: usize) -> f64 {
+        let nf = (failed as f64 - ef).max(0.0);
+        let passed = passed as f64;
+        let failed = failed as f64;
+
+        match self {
+            Formula::Ochiai => ratio(ef, ((ef + nf) * (ef + ep)).sqrt()),
+            Formula::Tarantula => {
+                let fail_ratio = ratio(ef, failed);
+                let pass_ratio = ratio(ep, passed);
+                ratio(fail_ratio, fail_ratio + pass_ratio)
+            },
+            // DStar with the customary exponent of 2. A region covered by every
+            // failing and no passing execution has a zero denominator; it is
+            // as suspicious as a region can get.
+            Formula::DStar => {
+                if ep + nf == 0.0 {
+                    if ef > 0.0 { f64::MAX } else { 0.0 }
+                } else {
+                    ef * ef / (ep + nf)
+                }
+            },
+            Formula::Op2 => ef - ep / (passed + 1.0),
+            Formula::Jaccard => ratio(ef, ef + nf + ep),
+        }
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 { 0.0 } else { numerator / denominator }
+}
+
+impl Display for Formula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Formula::Ochiai => write!(f, "ochiai"),
+            Formula::Tarantula => write!(f, "tarantula"),
+            Formula::DStar => write!(f, "dstar"),
+            Formula::Op2 => write!(f, "op2"),
+            Formula::Jaccard => write!(f, "jaccard"),
+        }
+    }
+}
+
+impl FromStr for Formula {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ochiai" => Ok(Formula::Ochiai),
+            "tarantula" => Ok(Formula::Tarantula),
+            "dstar" | "d*" => Ok(Formula::DStar),
+            "op2" => Ok(Formula::Op2),
+            "jaccard" => Ok(Formula::Jaccard),
+            _ => Err(format!(
+                "Unknown formula: {} (available: ochiai, tarantula, dstar, op2, jaccard)",
+                s
+            )),
+        }
+    }
+}
+
+/// A region together with its spectrum and its score under some formula.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Ranked {
+    pub rank: usize,
+    pub region: Region,
+    pub function: Option<String>,
+    pub ef: usize,
+    pub ep: usize,
+    pub nf: usize,
+    pub np: usize,
+    pub score: f64,
+}
+
+/// Ranks every region of the spectrum from most to least suspicious. Ties keep
+/// source order, so the ranking is deterministic.
+pub fn rank(spectrum: &Spectrum, formula: Formula) -> Vec<Ranked> {
+    let mut ranked = spectrum
+        .regions
+        .iter()
+        .map(|(region, counts)| Ranked {
+            rank: 0,
+            region: region.clone(),
+            function: spectrum.functions.get(region).cloned(),
+            ef: counts.ef,
+            ep: counts.ep,
+            nf: spectrum.failed - counts.ef,
+            np: spectrum.passed - counts.ep,
+            score: match spectrum.weights.get(region) {
+                Some(weights) => {
+                    formula.weighted(weights.ef, weights.ep, spectrum.passed, spectrum.failed)
+                },
+                None => formula.score(*counts, spectrum.passed, spectrum.failed),
+            },
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    for (i, r) in ranked.iter_mut().enumerate() {
+        r.rank = i + 1;
+    }
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::fixtures::spectrum;
+
+    fn counts(ef: usize, ep: usize) -> RegionCounts {
+        RegionCounts { ef, ep }
+    }
+
+    #[test]
+    fn test_formula_values() {
+        // 4 failing, 6 passing; the region is covered by 3 failing and 2 passing.
+        let c = counts(3, 2);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(Formula::Ochiai.score(c, 6, 4), 3.0 / (4.0f64 * 5.0).sqrt()));
+        assert!(close(Formula::Tarantula.score(c, 6, 4), 0.75 / (0.75 + 2.0 / 6.0)));
+        assert!(close(Formula::DStar.score(c, 6, 4), 9.0 / 3.0));
+        assert!(close(Formula::Op2.score(c, 6, 4), 3.0 - 2.0 / 7.0));
+        assert!(close(Formula::Jaccard.score(c, 6, 4), 3.0 / 6.0));
+    }
+
+    #[test]
+    fn test_formula_degenerate_spectra() {
+        for formula in Formula::ALL {
+            assert_eq!(formula.score(counts(0, 0), 0, 0), 0.0, "{}", formula);
+            assert!(formula.score(counts(0, 0), 3, 2).is_finite(), "{}", formula);
+        }
+        assert_eq!(Formula::DStar.score(counts(2, 0), 5, 2), f64::MAX);
+    }
+
+    #[test]
+    fn test_formula_from_str() {
+        for formula in Formula::ALL {
+            assert_eq!(formula.to_string().parse::<Formula>(), Ok(formula));
+        }
+        assert_eq!("Ochiai".parse::<Formula>(), Ok(Formula::Ochiai));
+        assert_eq!("D*".parse::<Formula>(), Ok(Formula::DStar));
+        assert!("delta".parse::<Formula>().is_err());
+    }
+
+    #[test]
+    fn test_rank_orders_by_score() {
+        let spectrum = spectrum(2, 2, &[(1, 2, 2), (2, 2, 0), (3, 0, 2)]);
+
+        let ranked = rank(&spectrum, Formula::Ochiai);
+        let lines = ranked.iter().map(|r| r.region.start_line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 1, 3]);
+        assert_eq!(ranked[0].rank, 1);
+        assert_eq!((ranked[2].nf, ranked[2].np), (2, 0));
+    }
+}
@@ -0,0 +1,407 @@
+//! A self-contained HTML report of a source file with every coverage region
+//! tinted by its suspiciousness, for reading a ranking against the code
+//! rather than as `file:line:col` tuples.
+//!
+//! The report needs nothing but a browser: styles are inline, and the counts
+//! and scores of a region show up as the native tooltip of its code.
+
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+use faultloc_mutants::marauder::{self, Block};
+
+use crate::{
+    coverage::Region,
+    formula::{Formula, Ranked},
+};
+
+/// A region with its spectrum and its score under every formula, ranked by
+/// the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub rank: usize,
+    pub region: Region,
+    pub function: Option<String>,
+    pub ef: usize,
+    pub ep: usize,
+    pub nf: usize,
+    pub np: usize,
+    pub scores: Vec<(Formula, f64)>,
+}
+
+impl Cell {
+    /// The score the region is tinted by.
+    pub fn score(&self) -> f64 {
+        self.scores[0].1
+    }
+
+    /// Whether `(line, col)` lies in the region.
+    fn covers(&self, line: u32, col: u32) -> bool {
+        let span = self.region.span();
+        (span.start.line, span.start.col) <= (line, col)
+            && (line, col) <= (span.end.line, span.end.col)
+    }
+
+    /// The tooltip of the region.
+    fn describe(&self, passed: usize, failed: usize) -> String {
+        let mut text = self.region.to_string();
+        if let Some(function) = &self.function {
+            write!(text, " in {}", function).unwrap();
+        }
+        write!(text, "\nrank {}", self.rank).unwrap();
+        write!(text, "\nfailing {} of {}, passing {} of {}", self.ef, failed, self.ep, passed)
+            .unwrap();
+        for (formula, score) in &self.scores {
+            write!(text, "\n{} {:.4}", formula, score).unwrap();
+        }
+        text
+    }
+}
+
+/// Joins rankings of the same regions under several formulas. The first
+/// ranking sets the order and the counts.
+pub fn cells(rankings: &[(Formula, Vec<Ranked>)]) -> Vec<Cell> {
+    let Some((_, first)) = rankings.first() else {
+        return vec![];
+    };
+    let scores = rankings
+        .iter()
+        .map(|(formula, ranked)| {
+            (*formula, ranked.iter().map(|r| (&r.region, r.score)).collect::<HashMap<_, _>>())
+        })
+        .collect::<Vec<_>>();
+    first
+        .iter()
+        .map(|r| Cell {
+            rank: r.rank,
+            region: r.region.clone(),
+            function: r.function.clone(),
+            ef: r.ef,
+            ep: r.ep,
+            nf: r.nf,
+            np: r.np,
+            scores: scores
+                .iter()
+                .map(|(formula, scores)| {
+                    (*formula, scores.get(&r.region).copied().unwrap_or_default())
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// What the report shows besides the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub title: String,
+    /// The inputs that passed and failed.
+    pub passed: usize,
+    pub failed: usize,
+    /// The regions of every file, most suspicious first.
+    pub cells: Vec<Cell>,
+    /// The mutants whose blocks are outlined; other blocks are outlined
+    /// faintly.
+    pub mutants: Vec<String>,
+    /// How many of the most suspicious regions to list above the source.
+    pub top: usize,
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { padding: 0.2em 0.8em; text-align: left; border-bottom: 1px solid #ddd; }
+td.num { text-align: right; font-family: monospace; }
+pre { font-size: 13px; line-height: 1.4; }
+.line { display: block; border-left: 2px solid transparent; border-right: 2px solid transparent; }
+.n { display: inline-block; width: 4em; padding-right: 1em; text-align: right; color: #999; user-select: none; }
+.n a { color: inherit; text-decoration: none; }
+.region { border-radius: 2px; }
+.region:hover { outline: 1px solid #444; }
+.unexecuted { color: #999; }
+.block { border-left-style: dashed; border-right-style: dashed; border-color: #888; }
+.block.first { border-top: 2px dashed #888; }
+.block.last { border-bottom: 2px dashed #888; }
+.block.mutant { border-left-style: solid; border-right-style: solid; border-color: #1f4fd1; }
+.block.mutant.first { border-top: 2px solid #1f4fd1; }
+.block.mutant.last { border-bottom: 2px solid #1f4fd1; }
+.legend span { display: inline-block; width: 3em; text-align: center; font-size: 12px; }
+";
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The tint of a region scored `t`, from 0 for the least to 1 for the most
+/// suspicious region.
+fn tint(t: f64) -> String {
+    if t <= 0.0 {
+        "transparent".to_string()
+    } else {
+        format!("rgba(220, 38, 38, {:.2})", 0.08 + 0.72 * t.min(1.0))
+    }
+}
+
+/// How suspicious each cell is relative to the others, from 0 to 1. DStar
+/// scores regions only failing inputs reach as `f64::MAX`; those are the
+/// most suspicious and the rest are scaled without them.
+fn intensities(cells: &[&Cell]) -> Vec<f64> {
+    let finite = cells.iter().map(|cell| cell.score()).filter(|score| *score < f64::MAX);
+    let (min, max) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), score| {
+        (min.min(score), max.max(score))
+    });
+    let min = min.min(0.0);
+    cells
+        .iter()
+        .map(|cell| match cell.score() {
+            score if score >= f64::MAX => 1.0,
+            _ if max <= min => 0.0,
+            score => (score - min) / (max - min),
+        })
+        .collect()
+}
+
+/// Coverage exports carry absolute paths, and the source is given relative to
+/// wherever the report is made from, so a region is in the source when the
+/// paths end alike: in full, or in the file and the directory holding it.
+fn same_file(region: &Region, file: &str) -> bool {
+    let (a, b) = (Path::new(&region.file), Path::new(file));
+    let shared = a.components().rev().zip(b.components().rev()).take_while(|(a, b)| a == b).count();
+    let shortest = a.components().count().min(b.components().count());
+    shared > 0 && shared >= shortest.min(2)
+}
+
+/// Renders `source`, read from `file`, with the regions of `report` in it.
+pub fn render(report: &Report, file: &str, source: &str) -> Result<String, String> {
+    let blocks = marauder::parse(source).map_err(|e| format!("{}:{}", file, e))?;
+    let cells =
+        report.cells.iter().filter(|cell| same_file(&cell.region, file)).collect::<Vec<_>>();
+    let intensities = intensities(&cells);
+    let formula = report.cells.first().map(|cell| cell.scores[0].0).unwrap_or(Formula::Ochiai);
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(
+        html,
+        "<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>",
+        escape(&report.title),
+        STYLE
+    )
+    .unwrap();
+    writeln!(html, "<h1>{}</h1>", escape(&report.title)).unwrap();
+    writeln!(
+        html,
+        "<p>{} &middot; {} passing and {} failing inputs &middot; tinted by {}, hover a region for its counts and scores</p>",
+        escape(file),
+        report.passed,
+        report.failed,
+        formula
+    )
+    .unwrap();
+    write!(html, "<p class=\"legend\">").unwrap();
+    for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        write!(html, "<span style=\"background: {}\">{:.2}</span>", tint(t), t).unwrap();
+    }
+    writeln!(html, "</p>").unwrap();
+
+    writeln!(html, "<table>\n<tr><th>Rank</th><th>Region</th><th>Function</th><th>ef</th><th>ep</th><th>{}</th></tr>", formula)
+        .unwrap();
+    for cell in cells.iter().take(report.top) {
+        writeln!(
+            html,
+            "<tr><td class=\"num\">{}</td><td><a href=\"#L{}\">{}</a></td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.4}</td></tr>",
+            cell.rank,
+            cell.region.start_line,
+            escape(&cell.region.to_string()),
+            escape(cell.function.as_deref().unwrap_or("-")),
+            cell.ef,
+            cell.ep,
+            cell.score()
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>\n<pre>").unwrap();
+
+    for (i, text) in source.lines().enumerate() {
+        let line = i as u32 + 1;
+        write!(html, "<span class=\"{}\"", line_class(&blocks, &report.mutants, line)).unwrap();
+        match blocks.iter().find(|block| block.span.start.line == line) {
+            Some(block) => write!(html, " title=\"{}\">", escape(&describe(block))).unwrap(),
+            None => write!(html, ">").unwrap(),
+        }
+        write!(html, "<span class=\"n\" id=\"L{0}\"><a href=\"#L{0}\">{0}</a></span>", line)
+            .unwrap();
+
+        let here = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.region.start_line <= line && line <= cell.region.end_line);
+        let here = here.collect::<Vec<_>>();
+        let mut open = None;
+        for (offset, c) in text.char_indices() {
+            // The innermost region holds the position.
+            let col = offset as u32 + 1;
+            let cell = here
+                .iter()
+                .filter(|(_, cell)| cell.covers(line, col))
+                .max_by_key(|(_, cell)| {
+                    let region = &cell.region;
+                    (
+                        (region.start_line, region.start_col),
+                        std::cmp::Reverse((region.end_line, region.end_col)),
+                    )
+                })
+                .map(|(j, _)| *j);
+            if cell != open {
+                if open.is_some() {
+                    write!(html, "</span>").unwrap();
+                }
+                if let Some(j) = cell {
+                    let class =
+                        if cells[j].ef + cells[j].ep == 0 { "region unexecuted" } else { "region" };
+                    write!(
+                        html,
+                        "<span class=\"{}\" style=\"background: {}\" title=\"{}\">",
+                        class,
+                        tint(intensities[j]),
+                        escape(&cells[j].describe(report.passed, report.failed))
+                    )
+                    .unwrap();
+                }
+                open = cell;
+            }
+            write!(html, "{}", escape(&c.to_string())).unwrap();
+        }
+        if open.is_some() {
+            write!(html, "</span>").unwrap();
+        }
+        writeln!(html, "</span>").unwrap();
+    }
+    writeln!(html, "</pre>\n</body>\n</html>").unwrap();
+    Ok(html)
+}
+
+/// The classes of a source line: whether it lies in a marauder block, and
+/// whether the block declares one of `mutants`.
+fn line_class(blocks: &[Block], mutants: &[String], line: u32) -> String {
+    let mut class = "line".to_string();
+    if let Some(block) =
+        blocks.iter().find(|block| block.span.start.line <= line && line <= block.span.end.line)
+    {
+        class.push_str(" block");
+        if mutants.iter().any(|mutant| block.mutant(mutant).is_some()) {
+            class.push_str(" mutant");
+        }
+        if block.span.start.line == line {
+            class.push_str(" first");
+        }
+        if block.span.end.line == line {
+            class.push_str(" last");
+        }
+    }
+    class
+}
+
+fn describe(block: &Block) -> String {
+    let name = if block.name.is_empty() { "anonymous" } else { &block.name };
+    let mutants = block.mutants().filter_map(|v| v.name.as_deref()).collect::<Vec<_>>();
+    let live = block.active().and_then(|v| v.name.as_deref()).unwrap_or("base");
+    format!("block {}: {} (live: {})", name, mutants.join(", "), live)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coverage::RegionCounts, coverage::Spectrum, formula};
+
+    const SOURCE: &str = "fn insert(k: i32, k2: i32) -> i32 {
+    if k < k2 {
+        /*| insert */
+        k + 1
+        /*|| insert_1 */
+        /*|
+        k + 2
+        */
+        /* |*/
+    } else {
+        k
+    }
+}
+";
+
+    fn region(start_line: u32, start_col: u32, end_line: u32, end_col: u32) -> Region {
+        Region {
+            file: "/home/w/BST/src/implementation.rs".to_string(),
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        }
+    }
+
+    fn report() -> Report {
+        let mut spectrum = Spectrum { passed: 3, failed: 1, ..Default::default() };
+        for (region, ef, ep) in [
+            (region(1, 35, 13, 2), 1, 3),
+            (region(2, 15, 10, 6), 1, 1),
+            (region(10, 12, 12, 6), 0, 2),
+            (region(20, 1, 20, 5), 0, 0),
+        ] {
+            spectrum.regions.insert(region, RegionCounts { ef, ep });
+        }
+        let rankings = [Formula::Tarantula, Formula::Ochiai]
+            .into_iter()
+            .map(|f| (f, formula::rank(&spectrum, f)))
+            .collect::<Vec<_>>();
+        Report {
+            title: "BST insert_1".to_string(),
+            passed: 3,
+            failed: 1,
+            cells: cells(&rankings),
+            mutants: vec!["insert_1".to_string()],
+            top: 2,
+        }
+    }
+
+    #[test]
+    fn test_cells() {
+        let cells = report().cells;
+        assert_eq!(cells.iter().map(|c| c.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(cells[0].region.start_line, 2);
+        assert_eq!(cells[0].scores[0], (Formula::Tarantula, 0.75));
+        assert_eq!(cells[0].scores[1].0, Formula::Ochiai);
+        assert!((cells[0].scores[1].1 - 0.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(intensities(&cells.iter().collect::<Vec<_>>()), vec![1.0, 2.0 / 3.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_render() {
+        let html = render(&report(), "workloads/Rust/BST/src/implementation.rs", SOURCE).unwrap();
+        assert!(!html.contains("<script") && !html.contains("http"));
+        // The branch is the innermost region of the comparison's line, and
+        // its tooltip carries the counts and both scores.
+        assert!(html.contains("if k &lt; k2 </span><span class=\"region\" style=\"background: rgba(220, 38, 38, 0.80)\" title=\"implementation.rs:2:15 - 10:6&#10;rank 1"));
+        assert!(
+            html.contains("failing 1 of 1, passing 1 of 3&#10;tarantula 0.7500&#10;ochiai 0.7071")
+        );
+        assert!(html.contains(
+            "<span class=\"line block mutant first\" title=\"block insert: insert_1 (live: base)\">"
+        ));
+        assert!(html.contains("<span class=\"line block mutant last\">"));
+        assert!(html.contains("<span class=\"region\" style=\"background: transparent\""));
+        // Only the top two regions are listed, and the region past the end
+        // of the file is left out.
+        assert_eq!(html.matches("<tr><td").count(), 2);
+        assert!(!html.contains("20:1"));
+        assert!(!render(&report(), "src/spec.rs", SOURCE).unwrap().contains("class=\"region\""));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod aggregate;
+pub mod cluster;
+pub mod coverage;
+pub mod divergence;
+pub mod export;
+pub mod filter;
+pub mod formula;
+pub mod heatmap;
+pub mod mbfl;
+pub mod model;
+pub mod query;
+pub mod score;
+pub mod significance;
+pub mod trace;
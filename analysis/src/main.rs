@@ -0,0 +1,1324 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use serde::Serialize;
+
+use faultloc_analysis::{
+    aggregate::{self, Weighting},
+    cluster::{self, Clustering, Failure},
+    coverage::{self, Spectrum},
+    divergence, export,
+    filter::Filter,
+    formula::{self, Formula, Ranked},
+    heatmap,
+    mbfl::{self, Method},
+    model::Model,
+    query::{self, Format, Selection, Table},
+    score::{GroundTruth, Score, Summary, TOP_N, Ties},
+    significance::{self, Options},
+    trace::{self, SequenceSpectrum},
+};
+use faultloc_store::{Reader, Trial};
+
+fn usage(program: &str) {
+    eprintln!(
+        "Usage: {} rank <jsondata/demangled> [--formula <name>] [--top <n>] [--model <name>] [--contrastive] [--excluded] [--filter <toml>]... [--json]",
+        program
+    );
+    eprintln!(
+        "       {} score <jsondata/demangled>... --mutant <name> [--source <implementation.rs>] [--formula <name>] [--ties <policy>] [--model <name>] [--contrastive] [--filter <toml>]... [--json]",
+        program
+    );
+    eprintln!(
+        "--contrastive ranks on the failing inputs and passing neighbors of faultloc --neighbors"
+    );
+    eprintln!(
+        "       {} aggregate <store.jsonl> --workload <name> --mutant <name>... [--strategy <name>] [--weight <scheme>] [--formula <name>] [--source <implementation.rs>] [--ties <policy>] [--top <n>] [--filter <toml>]... [--json]",
+        program
+    );
+    eprintln!(
+        "       {} cluster <store.jsonl> --workload <name> --mutant <name>... [--strategy <name>] [--by <clustering>] [--threshold <similarity>] [--formula <name>] [--source <implementation.rs>] [--ties <policy>] [--within <n>] [--top <n>] [--filter <toml>]... [--json]",
+        program
+    );
+    eprintln!(
+        "       {} significance <store.jsonl> --workload <name> --mutant <name>... [--strategy <name>] [--formula <name>] [--resamples <n>] [--confidence <level>] [--alpha <rate>] [--seed <n>] [--top <n>] [--filter <toml>]... [--json]",
+        program
+    );
+    eprintln!(
+        "       {} mbfl <mbfl.jsonl> --workload <name> --mutant <name>... [--strategy <name>] [--seed <n>] [--method <name>] [--source <implementation.rs>] [--store <store.jsonl>] [--formula <name>] [--ties <policy>] [--top <n>] [--filter <toml>]... [--json]",
+        program
+    );
+    eprintln!(
+        "mbfl ranks the locations faultloc-runner --mbfl mutated; with --source it scores them, and the store's SBFL ranking alongside"
+    );
+    eprintln!(
+        "       {} html <jsondata/demangled | store.jsonl> --source <implementation.rs> [--workload <name>] [--mutant <name>]... [--strategy <name>] [--formula <name>] [--top <n>] [--filter <toml>]... [--output <report.html>]",
+        program
+    );
+    eprintln!(
+        "html renders the source tinted by suspiciousness, with the blocks of the --mutant outlined; a store needs --workload and --mutant"
+    );
+    eprintln!(
+        "       {} sarif <jsondata/demangled | store.jsonl> [--workload <name>] [--mutant <name>]... [--strategy <name>] [--formula <name>] [--top <n>] [--root <dir>] [--filter <toml>]... [--output <report.sarif>]",
+        program
+    );
+    eprintln!(
+        "       {} lcov <jsondata/demangled | store.jsonl> [--workload <name>] [--mutant <name>]... [--strategy <name>] [--filter <toml>]... [--passing <lcov.info>] [--failing <lcov.info>]",
+        program
+    );
+    eprintln!(
+        "sarif reports the regions that score above zero, with file URIs relative to --root; lcov writes the coverage of the passing and the failing inputs apart"
+    );
+    eprintln!(
+        "       {} query <store.jsonl> [regions|pivot|exam] [--workload <name>]... [--mutant <name>]... [--property <name>]... [--strategy <name>]... [--since <YYYY-MM-DD>] [--until <YYYY-MM-DD>] [--formula <name>] [--ties <policy>] [--top <n>] [--workloads <dir>] [--filter <toml>]... [--format <table|csv|json>]",
+        program
+    );
+    eprintln!(
+        "query ranks each property's trials per mutation set: regions lists the top regions, pivot the rank of each mutant per property, exam the EXAM per workload; pivot and exam read the sources under --workloads (default: workloads)"
+    );
+    eprintln!(
+        "       {} sequences <traces> [--n <length>] [--formula <name>] [--top <n>] [--json]",
+        program
+    );
+    eprintln!(
+        "--filter keeps the regions the [faultloc] section of a marauder.toml or config.toml makes candidates; --excluded lists the rest"
+    );
+    eprintln!("Available formulas: ochiai (default), tarantula, dstar, op2, jaccard");
+    eprintln!(
+        "Available models: binary (default), counts, size (counts per input size), log (log counts)"
+    );
+    eprintln!("Available methods: metallaxis (default), muse");
+    eprintln!("Available weightings: equal (default), failures, specificity");
+    eprintln!("Available clusterings: coverage, shape, both (default), invariant");
+    eprintln!("Available tie policies: best, worst, average (default)");
+    eprintln!("Available formats: table (default), csv, json");
+}
+
+fn value<'a>(args: &mut impl Iterator<Item = &'a String>, flag: &str) -> Result<&'a str, String> {
+    args.next().map(String::as_str).ok_or_else(|| format!("Missing value for {}", flag))
+}
+
+fn number(value: &str, flag: &str) -> Result<usize, String> {
+    value.parse::<usize>().map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+/// The spectrum of the snapshots in `dir`, or of its near-miss pairs, under
+/// `model`.
+fn spectrum(dir: &str, contrastive: bool, model: Model) -> Result<Spectrum, String> {
+    let (mut spectrum, executions) = if contrastive {
+        let pairs = coverage::load_contrast_dir(Path::new(dir))?;
+        let executions = pairs.iter().flat_map(|(f, p)| [f, p]).cloned().collect::<Vec<_>>();
+        (Spectrum::from_pairs(&pairs), executions)
+    } else {
+        let executions = coverage::load_dir(Path::new(dir))?;
+        (Spectrum::from_executions(&executions), executions)
+    };
+    let unknown = executions.iter().filter(|execution| execution.size.is_none()).count();
+    if model == Model::Size && unknown > 0 {
+        eprintln!(
+            "Warning: {} of {} executions in {} have no recorded input size; their counts are not normalized",
+            unknown,
+            executions.len(),
+            dir
+        );
+    }
+    spectrum.weigh(&executions, model);
+    Ok(spectrum)
+}
+
+/// The `[faultloc]` sections of `files`, in order.
+fn filter(files: &[&str]) -> Result<Filter, String> {
+    let mut filter = Filter::default();
+    for file in files {
+        filter.extend(Filter::from_file(Path::new(file))?);
+    }
+    Ok(filter)
+}
+
+fn rank(args: &[String]) -> Result<(), String> {
+    let mut dir = None;
+    let mut formula = Formula::Ochiai;
+    let mut top = None;
+    let mut model = Model::Binary;
+    let mut contrastive = false;
+    let mut show_excluded = false;
+    let mut filters = vec![];
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--model" => model = value(&mut args, arg)?.parse()?,
+            "--contrastive" => contrastive = true,
+            "--excluded" => show_excluded = true,
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--json" => json = true,
+            _ if dir.is_none() && !arg.starts_with("--") => dir = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let dir = dir.ok_or("Missing coverage directory")?;
+    let filter = filter(&filters)?;
+
+    let (spectrum, excluded) = filter.split(&spectrum(dir, contrastive, model)?);
+    let mut ranked = formula::rank(&spectrum, formula);
+    if let Some(top) = top {
+        ranked.truncate(top);
+    }
+
+    if json {
+        let output = serde_json::json!({ "model": model, "formula": formula, "ranking": ranked });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "Formula: {}, model: {}, passing executions: {}, failing executions: {}",
+        formula, model, spectrum.passed, spectrum.failed
+    );
+    for r in ranked {
+        println!(
+            " {:>4} {:<40} | ef {:<5} ep {:<5} | {:.4}",
+            r.rank,
+            r.region.to_string(),
+            r.ef,
+            r.ep,
+            r.score
+        );
+    }
+    if !excluded.regions.is_empty() {
+        let failing = excluded.regions.values().filter(|counts| counts.ef > 0).count();
+        println!(
+            "Excluded by filter: {} regions, {} of them covered by failing executions",
+            excluded.regions.len(),
+            failing
+        );
+    }
+    // Where failing model-based properties diverged from their model, the
+    // keys to look for in the top regions.
+    for (failure, divergence) in divergence::load_dir(Path::new(dir))? {
+        let keys = divergence.keys.iter().map(i32::to_string).collect::<Vec<_>>();
+        println!(
+            "Diverging keys of failure {}: {} (first: {})",
+            failure,
+            keys.join(", "),
+            divergence.first.map_or("none".to_string(), |key| key.to_string())
+        );
+    }
+    if show_excluded {
+        for r in formula::rank(&excluded, formula) {
+            println!(
+                "    - {:<40} | ef {:<5} ep {:<5} | {:.4} | {}",
+                r.region.to_string(),
+                r.ef,
+                r.ep,
+                r.score,
+                r.function.as_deref().unwrap_or("-")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn score(args: &[String]) -> Result<(), String> {
+    let mut dirs = vec![];
+    let mut mutant = None;
+    let mut source = "src/implementation.rs";
+    let mut formula = Formula::Ochiai;
+    let mut ties = Ties::Average;
+    let mut model = Model::Binary;
+    let mut contrastive = false;
+    let mut filters = vec![];
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mutant" => mutant = Some(value(&mut args, arg)?),
+            "--source" => source = value(&mut args, arg)?,
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--ties" => ties = value(&mut args, arg)?.parse()?,
+            "--model" => model = value(&mut args, arg)?.parse()?,
+            "--contrastive" => contrastive = true,
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--json" => json = true,
+            _ if !arg.starts_with("--") => dirs.push(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    if dirs.is_empty() {
+        return Err("Missing coverage directory".to_string());
+    }
+    let mutant = mutant.ok_or("Missing --mutant")?;
+    let truth = GroundTruth::from_file(Path::new(source), mutant)?;
+    let filter = filter(&filters)?;
+
+    let mut scores = vec![];
+    for dir in &dirs {
+        let (spectrum, _) = filter.split(&spectrum(dir, contrastive, model)?);
+        scores.push(Score::new(&formula::rank(&spectrum, formula), &truth));
+    }
+    let summary = Summary::new(&scores, ties);
+
+    if json {
+        let output = serde_json::json!({ "model": model, "scores": scores, "summary": summary });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "Mutant: {}, block: {}, formula: {}, model: {}, ties: {}",
+        mutant, truth.span, formula, model, ties
+    );
+    for (dir, score) in dirs.iter().zip(&scores) {
+        let first_hit = match score.first_hit {
+            Some(hit) => format!("{} ({}-{})", hit.rank(ties), hit.best, hit.worst),
+            None => "-".to_string(),
+        };
+        println!(
+            " {:<40} | regions {:<5} faulty {:<3} | first hit {:<12} | EXAM {:.4}",
+            dir,
+            score.regions,
+            score.faulty,
+            first_hit,
+            score.exam(ties)
+        );
+    }
+    let top_n = TOP_N.iter().map(|n| format!("top-{} {}", n, summary.top_n[n])).collect::<Vec<_>>();
+    println!(
+        "Localized {}/{}, mean EXAM {:.4}, {}",
+        summary.localized,
+        summary.rankings,
+        summary.mean_exam,
+        top_n.join(", ")
+    );
+    Ok(())
+}
+
+/// The candidate regions of the spectrum a stored trial was recorded with.
+fn trial_spectrum(trial: &Trial, filter: &Filter) -> Spectrum {
+    filter.split(&Spectrum::from_trial(trial)).0
+}
+
+/// The spectrum of each property, which every trial of it adds to.
+fn property_spectra(trials: &[Trial], filter: &Filter) -> Vec<(String, Spectrum)> {
+    let mut spectra = Vec::<(String, Spectrum)>::new();
+    for trial in trials {
+        let spectrum = trial_spectrum(trial, filter);
+        match spectra.iter_mut().find(|(property, _)| *property == trial.key.property) {
+            Some((_, merged)) => merged.merge(&spectrum),
+            None => spectra.push((trial.key.property.clone(), spectrum)),
+        }
+    }
+    spectra
+}
+
+/// The stored trials of `workload` with exactly `mutants` switched on.
+fn trials(
+    store: &str,
+    workload: &str,
+    mutants: &[String],
+    strategy: Option<&str>,
+) -> Result<Vec<Trial>, String> {
+    let mut mutants = mutants.to_vec();
+    mutants.sort();
+    let mut trials = vec![];
+    for record in Reader::open(Path::new(store)).map_err(|e| e.to_string())? {
+        let trial = record.map_err(|e| format!("{}: {}", store, e))?.data;
+        let mut mutations = trial.key.mutations.clone();
+        mutations.sort();
+        if trial.key.workload == workload
+            && mutations == mutants
+            && strategy.is_none_or(|strategy| trial.key.strategy == strategy)
+        {
+            trials.push(trial);
+        }
+    }
+    if trials.is_empty() {
+        return Err(format!("No trials of {} with {} in {}", workload, mutants.join(","), store));
+    }
+    Ok(trials)
+}
+
+fn aggregate(args: &[String]) -> Result<(), String> {
+    let mut store = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut weighting = Weighting::Equal;
+    let mut formula = Formula::Ochiai;
+    let mut source = None;
+    let mut ties = Ties::Average;
+    let mut top = None;
+    let mut filters = vec![];
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--weight" => weighting = value(&mut args, arg)?.parse()?,
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--source" => source = Some(value(&mut args, arg)?),
+            "--ties" => ties = value(&mut args, arg)?.parse()?,
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--json" => json = true,
+            _ if store.is_none() && !arg.starts_with("--") => store = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let store = store.ok_or("Missing store")?;
+    let workload = workload.ok_or("Missing --workload")?;
+    if mutants.is_empty() {
+        return Err("Missing --mutant".to_string());
+    }
+    let filter = filter(&filters)?;
+
+    let spectra = property_spectra(&trials(store, workload, &mutants, strategy)?, &filter);
+
+    let weights = weighting.weights(&spectra);
+    let mut aggregated = aggregate::rank(&spectra, formula, weighting);
+    let score = match source {
+        Some(source) => {
+            let ranked = aggregated.iter().map(|a| a.ranked.clone()).collect::<Vec<_>>();
+            let mut scores = vec![];
+            for mutant in &mutants {
+                scores
+                    .push(Score::new(&ranked, &GroundTruth::from_file(Path::new(source), mutant)?));
+            }
+            Some(scores)
+        },
+        None => None,
+    };
+    if let Some(top) = top {
+        aggregated.truncate(top);
+    }
+
+    if json {
+        let weights = spectra.iter().map(|(p, _)| p).zip(&weights).collect::<BTreeMap<_, _>>();
+        let output =
+            serde_json::json!({ "weights": weights, "ranking": aggregated, "scores": score });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!("Formula: {}, weighting: {}", formula, weighting);
+    for ((property, spectrum), weight) in spectra.iter().zip(&weights) {
+        println!(
+            " {:<28} | passed {:<6} failed {:<6} | weight {:.4}",
+            property, spectrum.passed, spectrum.failed, weight
+        );
+    }
+    for a in &aggregated {
+        let mut contributions =
+            a.contributions.iter().filter(|(_, c)| **c > 0.0).collect::<Vec<_>>();
+        contributions.sort_by(|a, b| b.1.total_cmp(a.1));
+        let contributions = contributions
+            .iter()
+            .map(|(property, c)| format!("{} {:.4}", property, c))
+            .collect::<Vec<_>>();
+        println!(
+            " {:>4} {:<40} | {:.4} | {}",
+            a.ranked.rank,
+            a.ranked.region.to_string(),
+            a.ranked.score,
+            contributions.join(", ")
+        );
+    }
+    for score in score.iter().flatten() {
+        let first_hit = match score.first_hit {
+            Some(hit) => format!("{} ({}-{})", hit.rank(ties), hit.best, hit.worst),
+            None => "-".to_string(),
+        };
+        println!(
+            "Mutant {}: first hit {}, EXAM {:.4} ({} ties)",
+            score.mutant,
+            first_hit,
+            score.exam(ties),
+            ties
+        );
+    }
+    Ok(())
+}
+
+/// How a cluster's ranking scores against every active mutant, and which of
+/// them it finds within its first `--within` regions.
+#[derive(Serialize)]
+struct Found {
+    mutants: Vec<String>,
+    scores: Vec<Score>,
+}
+
+fn cluster(args: &[String]) -> Result<(), String> {
+    let mut store = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut by = Clustering::Both;
+    let mut threshold = 0.8;
+    let mut formula = Formula::Ochiai;
+    let mut source = None;
+    let mut ties = Ties::Average;
+    let mut within = 10;
+    let mut top = 5;
+    let mut filters = vec![];
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--by" => by = value(&mut args, arg)?.parse()?,
+            "--threshold" => {
+                let value = value(&mut args, arg)?;
+                threshold = value
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid value for {}: {}", arg, value))?;
+            },
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--source" => source = Some(value(&mut args, arg)?),
+            "--ties" => ties = value(&mut args, arg)?.parse()?,
+            "--within" => within = number(value(&mut args, arg)?, arg)?,
+            "--top" => top = number(value(&mut args, arg)?, arg)?,
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--json" => json = true,
+            _ if store.is_none() && !arg.starts_with("--") => store = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let store = store.ok_or("Missing store")?;
+    let workload = workload.ok_or("Missing --workload")?;
+    if mutants.is_empty() {
+        return Err("Missing --mutant".to_string());
+    }
+    let filter = filter(&filters)?;
+    let truths = match source {
+        Some(source) => mutants
+            .iter()
+            .map(|mutant| GroundTruth::from_file(Path::new(source), mutant))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+
+    // A trial is one faultloc run, so its failing coverage is one failure.
+    let trials = trials(store, workload, &mutants, strategy)?;
+    let spectra = trials.iter().map(|trial| trial_spectrum(trial, &filter)).collect::<Vec<_>>();
+    let failing = (0..trials.len()).filter(|i| spectra[*i].failed > 0).collect::<Vec<_>>();
+    let failures = failing
+        .iter()
+        .map(|&i| Failure {
+            id: format!("{} trial {}", trials[i].key.property, trials[i].key.trial),
+            signature: spectra[i]
+                .regions
+                .iter()
+                .filter(|(_, counts)| counts.ef > 0)
+                .map(|(region, _)| region.clone())
+                .collect(),
+            shape: cluster::counterexample(&trials[i].output).map(cluster::shape),
+            broken: cluster::broke(&trials[i].output),
+        })
+        .collect::<Vec<_>>();
+    let clusters = cluster::cluster(&failures, by, threshold);
+
+    // Each cluster is ranked on its own failures against every passing
+    // execution.
+    let mut passing = Spectrum::default();
+    for spectrum in &spectra {
+        passing.merge(&spectrum.passing());
+    }
+    let mut results = vec![];
+    for members in &clusters {
+        let mut spectrum = passing.clone();
+        for &member in members {
+            spectrum.merge(&spectra[failing[member]].failing());
+        }
+        let ranked = formula::rank(&spectrum, formula);
+        let scores = truths.iter().map(|truth| Score::new(&ranked, truth)).collect::<Vec<_>>();
+        let found = Found {
+            mutants: scores
+                .iter()
+                .filter(|score| score.top_n(within, ties))
+                .map(|score| score.mutant.clone())
+                .collect(),
+            scores,
+        };
+        results.push((members, ranked, found));
+    }
+    let found = mutants
+        .iter()
+        .filter(|mutant| results.iter().any(|(_, _, found)| found.mutants.contains(mutant)))
+        .collect::<Vec<_>>();
+
+    if json {
+        let clusters = results
+            .iter()
+            .map(|(members, ranked, found)| {
+                serde_json::json!({
+                    "failures": members.iter().map(|&i| &failures[i].id).collect::<Vec<_>>(),
+                    "shapes": members.iter().filter_map(|&i| failures[i].shape.as_ref()).collect::<BTreeSet<_>>(),
+                    "broken": members.iter().filter_map(|&i| failures[i].broken.as_ref()).collect::<BTreeSet<_>>(),
+                    "ranking": &ranked[..top.min(ranked.len())],
+                    "found": found,
+                })
+            })
+            .collect::<Vec<_>>();
+        let output = serde_json::json!({ "clusters": clusters, "found": found });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "{} failing of {} trials in {} clusters, by {} (threshold {}), formula: {}",
+        failures.len(),
+        trials.len(),
+        clusters.len(),
+        by,
+        threshold,
+        formula
+    );
+    for (i, (members, ranked, found)) in results.iter().enumerate() {
+        let ids = members.iter().map(|&i| failures[i].id.as_str()).collect::<Vec<_>>();
+        println!("Cluster {}: {}", i, ids.join(", "));
+        let shapes =
+            members.iter().filter_map(|&i| failures[i].shape.as_ref()).collect::<BTreeSet<_>>();
+        for shape in shapes {
+            println!("  shape {}", shape);
+        }
+        let broken =
+            members.iter().filter_map(|&i| failures[i].broken.as_ref()).collect::<BTreeSet<_>>();
+        for broken in broken {
+            let names = broken.iter().map(String::as_str).collect::<Vec<_>>();
+            println!("  broke {}", names.join(", "));
+        }
+        for r in ranked.iter().take(top) {
+            println!(
+                " {:>4} {:<40} | ef {:<5} ep {:<5} | {:.4}",
+                r.rank,
+                r.region.to_string(),
+                r.ef,
+                r.ep,
+                r.score
+            );
+        }
+        if !truths.is_empty() {
+            println!(
+                "  finds {}/{} faults within the top {}: {}",
+                found.mutants.len(),
+                mutants.len(),
+                within,
+                found.mutants.join(", ")
+            );
+        }
+    }
+    if !truths.is_empty() {
+        println!("Faults found by some cluster: {}/{}", found.len(), mutants.len());
+    }
+    Ok(())
+}
+
+/// Reports each region's score across the trials of a mutant with a
+/// bootstrap confidence interval and a permutation-test p-value, so that a
+/// region that only stands out in one run can be told from one that stands
+/// out in all of them.
+fn significance(args: &[String]) -> Result<(), String> {
+    let mut store = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut formula = Formula::Ochiai;
+    let mut options = Options::default();
+    let mut alpha = 0.05;
+    let mut top = None;
+    let mut filters = vec![];
+    let mut json = false;
+
+    let fraction = |value: &str, flag: &str| {
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|v| *v > 0.0 && *v < 1.0)
+            .ok_or_else(|| format!("Invalid value for {}: {}", flag, value))
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--resamples" => options.resamples = number(value(&mut args, arg)?, arg)?,
+            "--confidence" => options.confidence = fraction(value(&mut args, arg)?, arg)?,
+            "--alpha" => alpha = fraction(value(&mut args, arg)?, arg)?,
+            "--seed" => options.seed = number(value(&mut args, arg)?, arg)? as u64,
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--json" => json = true,
+            _ if store.is_none() && !arg.starts_with("--") => store = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let store = store.ok_or("Missing store")?;
+    let workload = workload.ok_or("Missing --workload")?;
+    if mutants.is_empty() {
+        return Err("Missing --mutant".to_string());
+    }
+    let filter = filter(&filters)?;
+
+    // Trials are resampled within their property.
+    let mut strata = Vec::<(String, Vec<Spectrum>)>::new();
+    let trials = trials(store, workload, &mutants, strategy)?;
+    for trial in &trials {
+        let spectrum = trial_spectrum(trial, &filter);
+        match strata.iter_mut().find(|(property, _)| *property == trial.key.property) {
+            Some((_, spectra)) => spectra.push(spectrum),
+            None => strata.push((trial.key.property.clone(), vec![spectrum])),
+        }
+    }
+    if strata.iter().all(|(_, spectra)| spectra.len() < 2) {
+        eprintln!(
+            "Warning: no property has more than one trial, so the intervals are points; set `trials` above 1"
+        );
+    }
+
+    let strata = strata.into_iter().map(|(_, spectra)| spectra).collect::<Vec<_>>();
+    let mut assessed = significance::assess(&strata, formula, options);
+    let significant = assessed.iter().filter(|a| a.q < alpha).count();
+    if let Some(top) = top {
+        assessed.truncate(top);
+    }
+
+    if json {
+        let output = serde_json::json!({
+            "formula": formula,
+            "trials": trials.len(),
+            "resamples": options.resamples,
+            "confidence": options.confidence,
+            "alpha": alpha,
+            "seed": options.seed,
+            "regions": assessed,
+        });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "Formula: {}, trials: {}, resamples: {}, confidence: {}, seed: {}",
+        formula,
+        trials.len(),
+        options.resamples,
+        options.confidence,
+        options.seed
+    );
+    for a in &assessed {
+        println!(
+            " {:>4} {:<40} | {:.4} [{:.4}, {:.4}] | rank {:.1}-{:.1} | p {:.4} q {:.4}{}",
+            a.rank,
+            a.region.to_string(),
+            a.score,
+            a.low,
+            a.high,
+            a.rank_low,
+            a.rank_high,
+            a.p,
+            a.q,
+            if a.q < alpha { " *" } else { "" }
+        );
+    }
+    println!("Significant at a false discovery rate of {}: {} regions", alpha, significant);
+    Ok(())
+}
+
+/// Ranks the locations of mutation-based fault localization and, given the
+/// source with the seeded faults, benchmarks both methods against SBFL on
+/// the same faults.
+fn mbfl(args: &[String]) -> Result<(), String> {
+    let mut results = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut seed = None;
+    let mut method = Method::Metallaxis;
+    let mut source = None;
+    let mut store = None;
+    let mut formula = Formula::Ochiai;
+    let mut ties = Ties::Average;
+    let mut top = None;
+    let mut filters = vec![];
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--seed" => seed = Some(number(value(&mut args, arg)?, arg)? as u64),
+            "--method" => method = value(&mut args, arg)?.parse()?,
+            "--source" => source = Some(value(&mut args, arg)?),
+            "--store" => store = Some(value(&mut args, arg)?),
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--ties" => ties = value(&mut args, arg)?.parse()?,
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--json" => json = true,
+            _ if results.is_none() && !arg.starts_with("--") => results = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let results = results.ok_or("Missing MBFL results")?;
+    let workload = workload.ok_or("Missing --workload")?;
+    if mutants.is_empty() {
+        return Err("Missing --mutant".to_string());
+    }
+    if store.is_some() && source.is_none() {
+        return Err("--store needs --source to score against".to_string());
+    }
+    mutants.sort();
+
+    let runs = mbfl::load(Path::new(results))?
+        .into_iter()
+        .filter(|run| {
+            let mut mutations = run.mutations.clone();
+            mutations.sort();
+            run.workload == workload
+                && mutations == mutants
+                && strategy.is_none_or(|strategy| run.strategy == strategy)
+                && seed.is_none_or(|seed| run.seed == seed)
+        })
+        .collect::<Vec<_>>();
+    if runs.is_empty() {
+        return Err(format!("No runs of {} with {} in {}", workload, mutants.join(","), results));
+    }
+    let file = source.unwrap_or("src/implementation.rs");
+    let mut ranking = mbfl::rank(&runs, file, method);
+    if ranking.failed == 0 {
+        eprintln!(
+            "Warning: no input fails with the seeded faults alone, so no mutant can point at them; run with more --tests"
+        );
+    }
+
+    // Each method, and SBFL on the stored trials, against every seeded fault.
+    let mut benchmark = BTreeMap::<String, Vec<Score>>::new();
+    if let Some(source) = source {
+        let truths = mutants
+            .iter()
+            .map(|mutant| GroundTruth::from_file(Path::new(source), mutant))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut rankings = [Method::Metallaxis, Method::Muse]
+            .into_iter()
+            .map(|method| (method.to_string(), mbfl::rank(&runs, file, method).ranked()))
+            .collect::<Vec<_>>();
+        if let Some(store) = store {
+            let filter = filter(&filters)?;
+            let spectra = property_spectra(&trials(store, workload, &mutants, strategy)?, &filter);
+            let aggregated = aggregate::rank(&spectra, formula, Weighting::Equal);
+            let ranked = aggregated.into_iter().map(|a| a.ranked).collect();
+            rankings.push((format!("sbfl {}", formula), ranked));
+        }
+        for (name, ranked) in rankings {
+            let scores = truths.iter().map(|truth| Score::new(&ranked, truth)).collect();
+            benchmark.insert(name, scores);
+        }
+    }
+    let locations = ranking.locations.len();
+    if let Some(top) = top {
+        ranking.locations.truncate(top);
+    }
+
+    if json {
+        let output = serde_json::json!({ "ranking": ranking, "benchmark": benchmark });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "Method: {}, {} mutants at {} locations, passing inputs: {}, failing inputs: {}",
+        method, ranking.mutants, locations, ranking.passed, ranking.failed
+    );
+    for l in &ranking.locations {
+        println!(
+            " {:>4} {:<40} | {:.4} | {:<12} | killed {}/{} | failing {:<5} passing {:<5}",
+            l.rank,
+            l.region.to_string(),
+            l.score,
+            l.function.as_deref().unwrap_or("-"),
+            l.killed,
+            l.mutants,
+            l.kills.failing,
+            l.kills.passing
+        );
+    }
+    for (name, scores) in &benchmark {
+        for score in scores {
+            let first_hit = match score.first_hit {
+                Some(hit) => format!("{} ({}-{})", hit.rank(ties), hit.best, hit.worst),
+                None => "-".to_string(),
+            };
+            println!(
+                "{:<14} | mutant {:<12} | ranked {:<5} | first hit {:<12} | EXAM {:.4} ({} ties)",
+                name,
+                score.mutant,
+                score.regions,
+                first_hit,
+                score.exam(ties),
+                ties
+            );
+        }
+    }
+    Ok(())
+}
+
+/// What a report is made from: the snapshots in a coverage directory, or the
+/// stored trials of a workload with exactly some mutants, a spectrum per
+/// property.
+struct Input {
+    title: String,
+    spectra: Vec<(String, Spectrum)>,
+    stored: bool,
+}
+
+impl Input {
+    fn load(
+        input: &str,
+        workload: Option<&str>,
+        mutants: &[String],
+        strategy: Option<&str>,
+        filter: &Filter,
+    ) -> Result<Input, String> {
+        if Path::new(input).is_dir() {
+            if workload.is_some() || strategy.is_some() {
+                return Err("--workload and --strategy only apply to a store".to_string());
+            }
+            let spectrum = filter.split(&spectrum(input, false, Model::Binary)?).0;
+            return Ok(Input {
+                title: input.to_string(),
+                spectra: vec![(input.to_string(), spectrum)],
+                stored: false,
+            });
+        }
+        let workload = workload.ok_or("Missing --workload")?;
+        if mutants.is_empty() {
+            return Err("Missing --mutant".to_string());
+        }
+        Ok(Input {
+            title: format!("{} {}", workload, mutants.join(",")),
+            spectra: property_spectra(&trials(input, workload, mutants, strategy)?, filter),
+            stored: true,
+        })
+    }
+
+    /// Ranked like `rank` ranks a directory and `aggregate` a store.
+    fn rank(&self, formula: Formula) -> Vec<Ranked> {
+        if self.stored {
+            let aggregated = aggregate::rank(&self.spectra, formula, Weighting::Equal);
+            aggregated.into_iter().map(|a| a.ranked).collect()
+        } else {
+            formula::rank(&self.spectra[0].1, formula)
+        }
+    }
+
+    /// Every property's spectrum in one.
+    fn merged(&self) -> Spectrum {
+        let mut merged = Spectrum::default();
+        for (_, spectrum) in &self.spectra {
+            merged.merge(spectrum);
+        }
+        merged
+    }
+}
+
+fn html(args: &[String]) -> Result<(), String> {
+    let mut input = None;
+    let mut source = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut formula = Formula::Ochiai;
+    let mut top = 10;
+    let mut filters = vec![];
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--source" => source = Some(value(&mut args, arg)?),
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--top" => top = number(value(&mut args, arg)?, arg)?,
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--output" => output = Some(value(&mut args, arg)?),
+            _ if input.is_none() && !arg.starts_with("--") => input = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let input = input.ok_or("Missing coverage directory or store")?;
+    let source = source.ok_or("Missing --source")?;
+    let filter = filter(&filters)?;
+
+    let input = Input::load(input, workload, &mutants, strategy, &filter)?;
+    // The formula the regions are tinted by comes first.
+    let rankings = std::iter::once(formula)
+        .chain(Formula::ALL.into_iter().filter(|f| *f != formula))
+        .map(|f| (f, input.rank(f)))
+        .collect::<Vec<_>>();
+    let merged = input.merged();
+
+    let report = heatmap::Report {
+        title: input.title,
+        passed: merged.passed,
+        failed: merged.failed,
+        cells: heatmap::cells(&rankings),
+        mutants,
+        top,
+    };
+    let text =
+        std::fs::read_to_string(source).map_err(|e| format!("failed to read {}: {}", source, e))?;
+    let html = heatmap::render(&report, source, &text)?;
+    match output {
+        Some(output) => std::fs::write(output, html)
+            .map_err(|e| format!("failed to write {}: {}", output, e))?,
+        None => print!("{}", html),
+    }
+    Ok(())
+}
+
+fn sarif(args: &[String]) -> Result<(), String> {
+    let mut input = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut formula = Formula::Ochiai;
+    let mut top = None;
+    let mut root = None;
+    let mut filters = vec![];
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--root" => root = Some(value(&mut args, arg)?),
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--output" => output = Some(value(&mut args, arg)?),
+            _ if input.is_none() && !arg.starts_with("--") => input = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let input = input.ok_or("Missing coverage directory or store")?;
+    let filter = filter(&filters)?;
+
+    let mut ranked = Input::load(input, workload, &mutants, strategy, &filter)?.rank(formula);
+    if let Some(top) = top {
+        ranked.truncate(top);
+    }
+    let log = export::sarif(&ranked, formula, root.map(Path::new));
+    let text = serde_json::to_string_pretty(&log).map_err(|e| e.to_string())?;
+    match output {
+        Some(output) => std::fs::write(output, text)
+            .map_err(|e| format!("failed to write {}: {}", output, e))?,
+        None => println!("{}", text),
+    }
+    Ok(())
+}
+
+fn lcov(args: &[String]) -> Result<(), String> {
+    let mut input = None;
+    let mut workload = None;
+    let mut mutants = vec![];
+    let mut strategy = None;
+    let mut filters = vec![];
+    let mut passing = None;
+    let mut failing = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => workload = Some(value(&mut args, arg)?),
+            "--mutant" => mutants.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => strategy = Some(value(&mut args, arg)?),
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--passing" => passing = Some(value(&mut args, arg)?),
+            "--failing" => failing = Some(value(&mut args, arg)?),
+            _ if input.is_none() && !arg.starts_with("--") => input = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let input = input.ok_or("Missing coverage directory or store")?;
+    if passing.is_none() && failing.is_none() {
+        return Err("Missing --passing or --failing".to_string());
+    }
+    let filter = filter(&filters)?;
+
+    let merged = Input::load(input, workload, &mutants, strategy, &filter)?.merged();
+    for (path, side) in [(passing, merged.passing()), (failing, merged.failing())] {
+        if let Some(path) = path {
+            std::fs::write(path, export::lcov(&side))
+                .map_err(|e| format!("failed to write {}: {}", path, e))?;
+        }
+    }
+    println!(
+        "{} passing and {} failing inputs over {} regions",
+        merged.passed,
+        merged.failed,
+        merged.regions.len()
+    );
+    Ok(())
+}
+
+/// The source of `workload` under `workloads`, whichever language it is in.
+fn implementation(workloads: &Path, workload: &str) -> Result<PathBuf, String> {
+    let languages = std::fs::read_dir(workloads)
+        .map_err(|e| format!("failed to read {}: {}", workloads.display(), e))?;
+    languages
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(workload).join("src/implementation.rs"))
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("No source of {} under {}", workload, workloads.display()))
+}
+
+fn query(args: &[String]) -> Result<(), String> {
+    let mut store = None;
+    let mut view = None;
+    let mut selection = Selection::default();
+    let mut formula = Formula::Ochiai;
+    let mut ties = Ties::Average;
+    let mut top = 10;
+    let mut workloads = "workloads";
+    let mut filters = vec![];
+    let mut format = Format::Table;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workload" => selection.workloads.push(value(&mut args, arg)?.to_string()),
+            "--mutant" => selection.mutants.push(value(&mut args, arg)?.to_string()),
+            "--property" => selection.properties.push(value(&mut args, arg)?.to_string()),
+            "--strategy" => selection.strategies.push(value(&mut args, arg)?.to_string()),
+            "--since" => selection.since = Some(query::date(value(&mut args, arg)?)?),
+            "--until" => selection.until = Some(query::date(value(&mut args, arg)?)?),
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--ties" => ties = value(&mut args, arg)?.parse()?,
+            "--top" => top = number(value(&mut args, arg)?, arg)?,
+            "--workloads" => workloads = value(&mut args, arg)?,
+            "--filter" => filters.push(value(&mut args, arg)?),
+            "--format" => format = value(&mut args, arg)?.parse()?,
+            _ if store.is_none() && !arg.starts_with("--") => store = Some(arg.as_str()),
+            _ if view.is_none() && !arg.starts_with("--") => view = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let store = store.ok_or("Missing store")?;
+    let filter = filter(&filters)?;
+
+    let mut trials = vec![];
+    for record in Reader::open(Path::new(store)).map_err(|e| e.to_string())? {
+        let trial = record.map_err(|e| format!("{}: {}", store, e))?.data;
+        if selection.matches(&trial) {
+            trials.push(trial);
+        }
+    }
+    if trials.is_empty() {
+        return Err(format!("No trials in {} match the query", store));
+    }
+    let groups = query::groups(&trials, &filter);
+
+    // Ground truths are read once per workload and mutant.
+    let mut truths = BTreeMap::<(String, String), GroundTruth>::new();
+    let truth = |workload: &str, mutant: &str| {
+        let key = (workload.to_string(), mutant.to_string());
+        if let Some(truth) = truths.get(&key) {
+            return Ok(truth.clone());
+        }
+        let source = implementation(Path::new(workloads), workload)?;
+        let truth = GroundTruth::from_file(&source, mutant)?;
+        truths.insert(key, truth.clone());
+        Ok(truth)
+    };
+
+    let rank = |rank: Option<f64>| rank.map(|r| r.to_string()).unwrap_or("-".to_string());
+    let (json, table) = match view.unwrap_or("regions") {
+        "regions" => {
+            let top = query::top_regions(&groups, formula, top);
+            let mut table = Table::new(&[
+                "workload",
+                "mutations",
+                "property",
+                "rank",
+                "region",
+                "function",
+                "ef",
+                "ep",
+                "score",
+            ]);
+            for group in &top {
+                for r in &group.ranking {
+                    table.push(vec![
+                        group.workload.clone(),
+                        group.mutations.join(","),
+                        group.property.clone(),
+                        r.rank.to_string(),
+                        r.region.to_string(),
+                        r.function.clone().unwrap_or("-".to_string()),
+                        r.ef.to_string(),
+                        r.ep.to_string(),
+                        format!("{:.4}", r.score),
+                    ]);
+                }
+            }
+            (serde_json::to_value(&top), table)
+        },
+        "pivot" => {
+            let pivot = query::pivot(&query::localize(&groups, formula, truth)?, ties);
+            let mut header = vec!["mutant"];
+            header.extend(pivot.properties.iter().map(String::as_str));
+            header.push("best");
+            let mut table = Table::new(&header);
+            for row in &pivot.rows {
+                let mut cells = vec![row.mutant.clone()];
+                cells.extend(row.ranks.iter().copied().map(rank));
+                cells.push(row.best.clone().unwrap_or("-".to_string()));
+                table.push(cells);
+            }
+            (serde_json::to_value(&pivot), table)
+        },
+        "exam" => {
+            let exam = query::exam(&query::localize(&groups, formula, truth)?, ties);
+            let mut header = vec!["workload", "rankings", "localized", "mean EXAM"];
+            let tops = TOP_N.map(|n| format!("top-{}", n));
+            header.extend(tops.iter().map(String::as_str));
+            let mut table = Table::new(&header);
+            for (workload, summary) in &exam {
+                let mut cells = vec![
+                    workload.clone(),
+                    summary.rankings.to_string(),
+                    summary.localized.to_string(),
+                    format!("{:.4}", summary.mean_exam),
+                ];
+                cells.extend(summary.top_n.values().map(|n| n.to_string()));
+                table.push(cells);
+            }
+            let exam = exam.into_iter().collect::<BTreeMap<_, _>>();
+            (serde_json::to_value(&exam), table)
+        },
+        view => return Err(format!("Unknown view: {} (available: regions, pivot, exam)", view)),
+    };
+    match format {
+        Format::Json => println!("{}", json.map_err(|e| e.to_string())?),
+        Format::Csv => print!("{}", table.csv()),
+        Format::Table => print!("{}", table.aligned()),
+    }
+    Ok(())
+}
+
+/// Ranks the call n-grams of the traces `faultloc --trace` left in a
+/// directory, and sums up how often each function was entered and exited.
+fn sequences(args: &[String]) -> Result<(), String> {
+    let mut dir = None;
+    let mut n = 2;
+    let mut formula = Formula::Ochiai;
+    let mut top = None;
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--n" => n = number(value(&mut args, arg)?, arg)?,
+            "--formula" => formula = value(&mut args, arg)?.parse()?,
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--json" => json = true,
+            _ if dir.is_none() && !arg.starts_with("--") => dir = Some(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let dir = dir.ok_or("Missing trace directory")?;
+    if n == 0 {
+        return Err("Invalid value for --n: 0".to_string());
+    }
+
+    let spectrum = SequenceSpectrum::from_traces(&trace::load_dir(Path::new(dir))?, n);
+    let mut ranked = trace::rank(&spectrum, formula);
+    if let Some(top) = top {
+        ranked.truncate(top);
+    }
+
+    if json {
+        let output = serde_json::json!({ "ngrams": ranked, "functions": spectrum.functions });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "Formula: {}, {}-grams, passing traces: {}, failing traces: {}",
+        formula, n, spectrum.passed, spectrum.failed
+    );
+    for r in ranked {
+        println!(
+            " {:>4} {:<60} | ef {:<5} ep {:<5} | {:.4}",
+            r.rank,
+            r.ngram.join(" -> "),
+            r.ef,
+            r.ep,
+            r.score
+        );
+    }
+    println!("Calls per function (enter/exit):");
+    for (function, counts) in &spectrum.functions {
+        println!(
+            "  {:<20} failing {}/{}, passing {}/{}",
+            function,
+            counts.failing.enter,
+            counts.failing.exit,
+            counts.passing.enter,
+            counts.passing.exit
+        );
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() < 2 {
+        usage(&args[0]);
+        return ExitCode::FAILURE;
+    }
+
+    let result = match args[1].as_str() {
+        "rank" => rank(&args[2..]),
+        "score" => score(&args[2..]),
+        "aggregate" => aggregate(&args[2..]),
+        "cluster" => cluster(&args[2..]),
+        "significance" => significance(&args[2..]),
+        "mbfl" => mbfl(&args[2..]),
+        "html" => html(&args[2..]),
+        "sarif" => sarif(&args[2..]),
+        "lcov" => lcov(&args[2..]),
+        "query" => query(&args[2..]),
+        "sequences" => sequences(&args[2..]),
+        command => Err(format!("Unknown command: {}", command)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            usage(&args[0]);
+            ExitCode::FAILURE
+        },
+    }
+}
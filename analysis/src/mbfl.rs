@@ -0,0 +1,380 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    path::Path,
+    str::FromStr,
+};
+
+use faultloc_mutants::operators::Mutation;
+use faultloc_store::Status;
+use serde::{Deserialize, Serialize};
+
+use crate::{coverage::Region, formula::Ranked};
+
+/// The outcomes of one property on the inputs of a seeded run, as
+/// `faultloc-runner --mbfl` records them, against the workload with its
+/// seeded faults alone or with a generated mutant on top.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MutantRun {
+    pub workload: String,
+    pub mutations: Vec<String>,
+    pub strategy: String,
+    pub property: String,
+    pub seed: u64,
+    /// `None` for the seeded faults alone.
+    pub mutant: Option<Mutation>,
+    pub status: Status,
+    pub time: f64,
+    /// `P`, `F` or `D` for each input that passed, failed or was discarded,
+    /// and `?` for those a crashed or killed run never got to.
+    pub outcomes: String,
+}
+
+/// Reads the runs of a results file, one JSON object per line.
+pub fn load(path: &Path) -> Result<Vec<MutantRun>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+/// Which inputs a mutant changed the outcome of. A mutant kills an input
+/// when the input does something else on it than on the seeded faults
+/// alone; inputs either run never got to are left out.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Kills {
+    /// Failing inputs killed.
+    pub failing: usize,
+    /// Passing inputs killed.
+    pub passing: usize,
+    /// Failing inputs that pass on the mutant.
+    pub f2p: usize,
+    /// Passing inputs that fail on the mutant.
+    pub p2f: usize,
+}
+
+impl Kills {
+    pub fn new(baseline: &str, mutant: &str) -> Kills {
+        let mut kills = Kills::default();
+        for (before, after) in baseline.chars().zip(mutant.chars()) {
+            if before == '?' || after == '?' || before == after {
+                continue;
+            }
+            match (before, after) {
+                ('F', 'P') => kills.f2p += 1,
+                ('P', 'F') => kills.p2f += 1,
+                _ => {},
+            }
+            match before {
+                'F' => kills.failing += 1,
+                'P' => kills.passing += 1,
+                _ => {},
+            }
+        }
+        kills
+    }
+
+    pub fn add(&mut self, other: Kills) {
+        self.failing += other.failing;
+        self.passing += other.passing;
+        self.f2p += other.f2p;
+        self.p2f += other.p2f;
+    }
+
+    pub fn killed(&self) -> bool {
+        self.failing + self.passing > 0
+    }
+}
+
+/// How the kills of the mutants at a location add up to its suspiciousness.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    /// The best Ochiai score of the location's mutants, with killing an input
+    /// in place of covering it (Papadakis and Le Traon).
+    Metallaxis,
+    /// How much more the location's mutants turn failing inputs into passing
+    /// ones than the other way around, against every mutant (Moon et al.).
+    Muse,
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Method::Metallaxis => write!(f, "metallaxis"),
+            Method::Muse => write!(f, "muse"),
+        }
+    }
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "metallaxis" => Ok(Method::Metallaxis),
+            "muse" => Ok(Method::Muse),
+            _ => Err(format!("Unknown method: {} (available: metallaxis, muse)", s)),
+        }
+    }
+}
+
+/// A mutated location with the mutants generated at it, their kills summed
+/// over every property, and its score.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Location {
+    pub rank: usize,
+    pub region: Region,
+    pub function: Option<String>,
+    pub mutants: usize,
+    /// How many of the mutants killed some input.
+    pub killed: usize,
+    pub kills: Kills,
+    pub score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Ranking {
+    pub method: Method,
+    /// The inputs that passed and failed with the seeded faults alone.
+    pub passed: usize,
+    pub failed: usize,
+    pub mutants: usize,
+    pub locations: Vec<Location>,
+}
+
+impl Ranking {
+    /// The locations as a region ranking, for [`crate::score::Score`]. Only
+    /// the regions and scores carry over; `ef` and `ep` are the kills.
+    pub fn ranked(&self) -> Vec<Ranked> {
+        self.locations
+            .iter()
+            .map(|location| Ranked {
+                rank: location.rank,
+                region: location.region.clone(),
+                function: location.function.clone(),
+                ef: location.kills.failing,
+                ep: location.kills.passing,
+                nf: 0,
+                np: 0,
+                score: location.score,
+            })
+            .collect()
+    }
+}
+
+/// The runs a mutant run is compared with: those of the same strategy and
+/// property on the same seed, and so on the same inputs.
+fn key(run: &MutantRun) -> (&str, &str, u64) {
+    (&run.strategy, &run.property, run.seed)
+}
+
+/// Ranks the locations of `file` the mutants of `runs` were generated at.
+/// Each mutant run is compared with the run of the seeded faults alone on the
+/// same strategy, property and seed; runs without one are left out.
+pub fn rank(runs: &[MutantRun], file: &str, method: Method) -> Ranking {
+    let mut baseline = BTreeMap::new();
+    let mut mutants = HashMap::<&Mutation, Vec<&MutantRun>>::new();
+    for run in runs {
+        match &run.mutant {
+            None => {
+                baseline.insert(key(run), run.outcomes.as_str());
+            },
+            Some(mutant) => mutants.entry(mutant).or_default().push(run),
+        }
+    }
+    let count = |outcome| baseline.values().map(|o| o.matches(outcome).count()).sum::<usize>();
+    let (passed, failed) = (count('P'), count('F'));
+
+    let mut kills = mutants
+        .into_iter()
+        .map(|(mutant, runs)| {
+            let mut kills = Kills::default();
+            for run in runs {
+                if let Some(before) = baseline.get(&key(run)) {
+                    kills.add(Kills::new(before, &run.outcomes));
+                }
+            }
+            (mutant, kills)
+        })
+        .collect::<Vec<_>>();
+    kills.sort_by(|(a, _), (b, _)| {
+        (a.range.start, a.range.end, &a.replacement).cmp(&(
+            b.range.start,
+            b.range.end,
+            &b.replacement,
+        ))
+    });
+
+    let f2p = kills.iter().map(|(_, k)| k.f2p).sum::<usize>() as f64;
+    let p2f = kills.iter().map(|(_, k)| k.p2f).sum::<usize>() as f64;
+    let score = |kills: &Kills| match method {
+        Method::Metallaxis => {
+            let killed = (failed * (kills.failing + kills.passing)) as f64;
+            if killed == 0.0 { 0.0 } else { kills.failing as f64 / killed.sqrt() }
+        },
+        Method::Muse => {
+            let fixed = if f2p > 0.0 { kills.f2p as f64 / f2p } else { 0.0 };
+            // Weighs breaking passing inputs so that both sum to the same.
+            let broken = if p2f > 0.0 { (f2p / p2f) * kills.p2f as f64 / p2f } else { 0.0 };
+            fixed - broken
+        },
+    };
+
+    let mut locations = Vec::<Location>::new();
+    for (mutant, kills) in &kills {
+        let region = Region::from_span(file, &mutant.span);
+        let location = match locations.iter_mut().find(|l| l.region == region) {
+            Some(location) => location,
+            None => {
+                locations.push(Location {
+                    rank: 0,
+                    region,
+                    function: mutant.function.clone(),
+                    mutants: 0,
+                    killed: 0,
+                    kills: Kills::default(),
+                    score: 0.0,
+                });
+                locations.last_mut().expect("just pushed")
+            },
+        };
+        location.mutants += 1;
+        location.killed += kills.killed() as usize;
+        location.kills.add(*kills);
+        location.score = match method {
+            Method::Metallaxis => location.score.max(score(kills)),
+            Method::Muse => location.score + score(kills),
+        };
+    }
+    if method == Method::Muse {
+        for location in &mut locations {
+            location.score /= (location.mutants + 1) as f64;
+        }
+    }
+
+    // A stable sort keeps source order among ties, like `formula::rank`.
+    locations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    for (i, location) in locations.iter_mut().enumerate() {
+        location.rank = i + 1;
+    }
+    Ranking { method, passed, failed, mutants: kills.len(), locations }
+}
+
+#[cfg(test)]
+mod tests {
+    use faultloc_mutants::{
+        marauder::{Position, Span},
+        operators::Operator,
+    };
+
+    use super::*;
+
+    fn mutant(line: u32, replacement: &str) -> Mutation {
+        Mutation {
+            operator: Operator::FlipComparison,
+            function: Some("insert".to_string()),
+            span: Span { start: Position { line, col: 10 }, end: Position { line, col: 10 } },
+            range: line as usize * 100..line as usize * 100 + 1,
+            original: "<".to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    fn run(property: &str, mutant: Option<Mutation>, outcomes: &str) -> MutantRun {
+        seeded(property, 0, mutant, outcomes)
+    }
+
+    fn seeded(property: &str, seed: u64, mutant: Option<Mutation>, outcomes: &str) -> MutantRun {
+        MutantRun {
+            workload: "BST".to_string(),
+            mutations: vec!["insert_1".to_string()],
+            strategy: "crabcheck".to_string(),
+            property: property.to_string(),
+            seed,
+            mutant,
+            status: Status::Finished,
+            time: 0.1,
+            outcomes: outcomes.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_kills() {
+        let kills = Kills::new("PPFFD?P", "PFPDDPD");
+        assert_eq!(kills, Kills { failing: 2, passing: 2, f2p: 1, p2f: 1 });
+        assert!(!Kills::new("PF?", "PF?").killed());
+    }
+
+    #[test]
+    fn test_rank() {
+        // Line 1 holds the fault: mutating it fixes the failing inputs. Line 2
+        // breaks the passing ones, and line 3 changes nothing.
+        let runs = vec![
+            run("InsertPost", None, "PPFF"),
+            run("InsertModel", None, "PPPF"),
+            run("InsertPost", Some(mutant(1, ">=")), "PPPP"),
+            run("InsertModel", Some(mutant(1, ">=")), "PPPP"),
+            run("InsertPost", Some(mutant(2, ">=")), "FFDF"),
+            run("InsertModel", Some(mutant(2, ">=")), "FFFF"),
+            run("InsertPost", Some(mutant(2, ">")), "PPFF"),
+            run("InsertModel", Some(mutant(2, ">")), "PPP?"),
+            run("InsertPost", Some(mutant(3, ">=")), "PPFF"),
+            run("InsertModel", Some(mutant(3, ">=")), "PPPF"),
+        ];
+        // Metallaxis still blames line 2 a little for changing a failing
+        // input; MUSE holds breaking passing ones against it.
+        for (method, expected) in [(Method::Metallaxis, [1, 2, 3]), (Method::Muse, [1, 3, 2])] {
+            let ranking = rank(&runs, "src/implementation.rs", method);
+            assert_eq!((ranking.passed, ranking.failed, ranking.mutants), (5, 3, 4));
+            let lines = ranking.locations.iter().map(|l| l.region.start_line).collect::<Vec<_>>();
+            assert_eq!(lines, expected, "{}", method);
+            assert_eq!(ranking.locations[0].kills.f2p, 3);
+            let line = |n| ranking.locations.iter().find(|l| l.region.start_line == n).unwrap();
+            assert_eq!((line(2).mutants, line(2).killed), (2, 1));
+        }
+
+        let metallaxis = rank(&runs, "src/implementation.rs", Method::Metallaxis);
+        assert_eq!(metallaxis.locations[0].score, 1.0);
+        assert_eq!(metallaxis.locations[2].score, 0.0);
+        let ranked = metallaxis.ranked();
+        assert_eq!((ranked[0].ef, ranked[0].ep, ranked[0].score), (3, 0, 1.0));
+        // A one-character mutant covers its column, which `llvm-cov` regions
+        // end one past.
+        assert_eq!((ranked[0].region.start_col, ranked[0].region.end_col), (10, 11));
+        assert_eq!(ranked[0].region.span(), mutant(1, ">=").span);
+    }
+
+    #[test]
+    fn test_rank_seeds() {
+        // Each seed draws other inputs, so each mutant run is compared with
+        // the baseline of its own seed: line 1 fixes the failing input of
+        // both, and line 2 changes neither.
+        let runs = vec![
+            seeded("InsertPost", 1, None, "PF"),
+            seeded("InsertPost", 2, None, "FP"),
+            seeded("InsertPost", 1, Some(mutant(1, ">=")), "PP"),
+            seeded("InsertPost", 2, Some(mutant(1, ">=")), "PP"),
+            seeded("InsertPost", 1, Some(mutant(2, ">=")), "PF"),
+            seeded("InsertPost", 2, Some(mutant(2, ">=")), "FP"),
+            seeded("InsertPost", 3, Some(mutant(2, ">=")), "PP"),
+        ];
+        let ranking = rank(&runs, "src/implementation.rs", Method::Metallaxis);
+        assert_eq!((ranking.passed, ranking.failed, ranking.mutants), (2, 2, 2));
+        let lines = ranking.locations.iter().map(|l| l.region.start_line).collect::<Vec<_>>();
+        assert_eq!(lines, [1, 2]);
+        assert_eq!(ranking.locations[0].kills, Kills { failing: 2, passing: 0, f2p: 2, p2f: 0 });
+        assert!(!ranking.locations[1].kills.killed());
+
+        let mut other = seeded("InsertPost", 1, Some(mutant(2, ">=")), "PP");
+        other.strategy = "quickcheck".to_string();
+        let ranking =
+            rank(&[runs, vec![other]].concat(), "src/implementation.rs", Method::Metallaxis);
+        assert!(!ranking.locations[1].kills.killed());
+    }
+}
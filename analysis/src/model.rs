@@ -0,0 +1,68 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// How much an execution that reaches a region counts for it. Binary
+/// coverage counts every execution that reaches the region once; the count
+/// models weigh it by how often the region ran, so that they can tell a
+/// region the failing executions run far more often apart from one every
+/// execution touches. Recursive functions run more often on bigger inputs,
+/// which `Size` corrects for, and `Log` tames.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Model {
+    #[default]
+    Binary,
+    /// Raw hit counts.
+    Counts,
+    /// Hit counts divided by the size of the input.
+    Size,
+    /// `ln(1 + count)`.
+    Log,
+}
+
+impl Model {
+    pub const ALL: [Model; 4] = [Model::Binary, Model::Counts, Model::Size, Model::Log];
+
+    /// The weight of a region that ran `count` times in an execution on an
+    /// input of `size`. An unknown or empty size counts as 1.
+    pub fn weight(&self, count: u64, size: Option<usize>) -> f64 {
+        match self {
+            Model::Binary => {
+                if count > 0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            },
+            Model::Counts => count as f64,
+            Model::Size => count as f64 / size.unwrap_or(1).max(1) as f64,
+            Model::Log => (count as f64).ln_1p(),
+        }
+    }
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Model::Binary => write!(f, "binary"),
+            Model::Counts => write!(f, "counts"),
+            Model::Size => write!(f, "size"),
+            Model::Log => write!(f, "log"),
+        }
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "binary" => Ok(Model::Binary),
+            "counts" => Ok(Model::Counts),
+            "size" => Ok(Model::Size),
+            "log" => Ok(Model::Log),
+            _ => Err(format!("Unknown model: {} (available: binary, counts, size, log)", s)),
+        }
+    }
+}
@@ -0,0 +1,437 @@
+//! Questions about a whole store at once: which regions each property ranks
+//! highest, how far down each property ranks each mutant, and how well each
+//! workload is localized overall.
+
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use faultloc_store::Trial;
+use serde::Serialize;
+
+use crate::{
+    coverage::Spectrum,
+    filter::Filter,
+    formula::{self, Formula, Ranked},
+    score::{GroundTruth, Score, Summary, Ties},
+};
+
+/// Which stored trials a query covers. Empty lists select everything, and a
+/// trial is selected by a mutant when the mutant is one of its mutations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub workloads: Vec<String>,
+    pub mutants: Vec<String>,
+    pub properties: Vec<String>,
+    pub strategies: Vec<String>,
+    /// The first and last day, as `YYYY-MM-DD`, of the trials to select.
+    /// Trials without a date are left out when either is given.
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+/// Checks that `value` is a `YYYY-MM-DD` date.
+pub fn date(value: &str) -> Result<String, String> {
+    let parts = value.split('-').collect::<Vec<_>>();
+    let valid = parts.len() == 3
+        && [4, 2, 2]
+            .iter()
+            .zip(&parts)
+            .all(|(len, part)| part.len() == *len && part.chars().all(|c| c.is_ascii_digit()));
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!("Invalid date: {} (expected YYYY-MM-DD)", value))
+    }
+}
+
+impl Selection {
+    pub fn matches(&self, trial: &Trial) -> bool {
+        let key = &trial.key;
+        let any = |list: &[String], value: &String| list.is_empty() || list.contains(value);
+        let day = trial.date.as_deref().map(|date| &date[..date.len().min(10)]);
+        any(&self.workloads, &key.workload)
+            && (self.mutants.is_empty() || key.mutations.iter().any(|m| self.mutants.contains(m)))
+            && any(&self.properties, &key.property)
+            && any(&self.strategies, &key.strategy)
+            && self.since.as_deref().is_none_or(|since| day.is_some_and(|day| day >= since))
+            && self.until.as_deref().is_none_or(|until| day.is_some_and(|day| day <= until))
+    }
+}
+
+/// Every selected trial of one property against one set of mutations, in a
+/// single spectrum. Trials of different strategies add up.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub workload: String,
+    /// Sorted, so that the order they were switched on in does not matter.
+    pub mutations: Vec<String>,
+    pub property: String,
+    pub trials: usize,
+    pub spectrum: Spectrum,
+}
+
+/// Groups `trials` by workload, mutations and property, keeping the
+/// candidate regions of `filter`.
+pub fn groups(trials: &[Trial], filter: &Filter) -> Vec<Group> {
+    let mut groups = BTreeMap::<(String, Vec<String>, String), Group>::new();
+    for trial in trials {
+        let mut mutations = trial.key.mutations.clone();
+        mutations.sort();
+        let key = (trial.key.workload.clone(), mutations.clone(), trial.key.property.clone());
+        let group = groups.entry(key).or_insert_with(|| Group {
+            workload: trial.key.workload.clone(),
+            mutations,
+            property: trial.key.property.clone(),
+            trials: 0,
+            spectrum: Spectrum::default(),
+        });
+        group.trials += 1;
+        group.spectrum.merge(&filter.split(&Spectrum::from_trial(trial)).0);
+    }
+    groups.into_values().collect()
+}
+
+/// How one property's ranking localizes one of the mutants it ran against.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Localization {
+    pub workload: String,
+    pub mutations: Vec<String>,
+    pub property: String,
+    pub score: Score,
+}
+
+impl Localization {
+    /// The mutant, with the others it was switched on with.
+    pub fn label(&self) -> String {
+        let others = self.mutations.iter().filter(|m| **m != self.score.mutant);
+        let others = others.cloned().collect::<Vec<_>>();
+        if others.is_empty() {
+            format!("{} {}", self.workload, self.score.mutant)
+        } else {
+            format!("{} {} (with {})", self.workload, self.score.mutant, others.join(","))
+        }
+    }
+}
+
+/// Scores the ranking of every group against each of its mutants, finding
+/// where they live with `truth`.
+pub fn localize(
+    groups: &[Group],
+    formula: Formula,
+    mut truth: impl FnMut(&str, &str) -> Result<GroundTruth, String>,
+) -> Result<Vec<Localization>, String> {
+    let mut localizations = vec![];
+    for group in groups {
+        let ranked = formula::rank(&group.spectrum, formula);
+        for mutant in &group.mutations {
+            localizations.push(Localization {
+                workload: group.workload.clone(),
+                mutations: group.mutations.clone(),
+                property: group.property.clone(),
+                score: Score::new(&ranked, &truth(&group.workload, mutant)?),
+            });
+        }
+    }
+    Ok(localizations)
+}
+
+/// The first-hit rank of each mutant under each property; `None` where the
+/// property never ran against the mutant or never reached it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Pivot {
+    pub properties: Vec<String>,
+    pub rows: Vec<PivotRow>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PivotRow {
+    pub mutant: String,
+    pub ranks: Vec<Option<f64>>,
+    /// The property that ranks the mutant highest, the first one among ties.
+    pub best: Option<String>,
+}
+
+pub fn pivot(localizations: &[Localization], ties: Ties) -> Pivot {
+    let mut properties = localizations.iter().map(|l| l.property.clone()).collect::<Vec<_>>();
+    properties.sort();
+    properties.dedup();
+    let mut rows = BTreeMap::<String, Vec<Option<f64>>>::new();
+    for localization in localizations {
+        let ranks =
+            rows.entry(localization.label()).or_insert_with(|| vec![None; properties.len()]);
+        let column = properties.binary_search(&localization.property).expect("collected above");
+        ranks[column] = localization.score.first_hit.map(|hit| hit.rank(ties));
+    }
+    let rows = rows
+        .into_iter()
+        .map(|(mutant, ranks)| {
+            let best = ranks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, rank)| rank.map(|rank| (i, rank)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| properties[i].clone());
+            PivotRow { mutant, ranks, best }
+        })
+        .collect();
+    Pivot { properties, rows }
+}
+
+/// The localizations of each workload summed up.
+pub fn exam(localizations: &[Localization], ties: Ties) -> Vec<(String, Summary)> {
+    let mut scores = BTreeMap::<&str, Vec<Score>>::new();
+    for localization in localizations {
+        scores.entry(&localization.workload).or_default().push(localization.score.clone());
+    }
+    scores
+        .into_iter()
+        .map(|(workload, scores)| (workload.to_string(), Summary::new(&scores, ties)))
+        .collect()
+}
+
+/// The `top` regions of each group's ranking.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TopRegions {
+    pub workload: String,
+    pub mutations: Vec<String>,
+    pub property: String,
+    pub trials: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub ranking: Vec<Ranked>,
+}
+
+pub fn top_regions(groups: &[Group], formula: Formula, top: usize) -> Vec<TopRegions> {
+    groups
+        .iter()
+        .map(|group| {
+            let mut ranking = formula::rank(&group.spectrum, formula);
+            ranking.truncate(top);
+            TopRegions {
+                workload: group.workload.clone(),
+                mutations: group.mutations.clone(),
+                property: group.property.clone(),
+                trials: group.trials,
+                passed: group.spectrum.passed,
+                failed: group.spectrum.failed,
+                ranking,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns, for reading.
+    Table,
+    Csv,
+    Json,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Table => write!(f, "table"),
+            Format::Csv => write!(f, "csv"),
+            Format::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown format: {} (available: table, csv, json)", s)),
+        }
+    }
+}
+
+/// Rows of text to print as a table or as CSV.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: &[&str]) -> Table {
+        Table { header: header.iter().map(|h| h.to_string()).collect(), rows: vec![] }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// The table as CSV, quoting the fields that need it.
+    pub fn csv(&self) -> String {
+        let field = |text: &String| {
+            if text.contains([',', '"', '\n']) {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            } else {
+                text.clone()
+            }
+        };
+        std::iter::once(&self.header)
+            .chain(&self.rows)
+            .map(|row| row.iter().map(field).collect::<Vec<_>>().join(",") + "\n")
+            .collect()
+    }
+
+    /// The table with its columns aligned; numbers are aligned right.
+    pub fn aligned(&self) -> String {
+        let mut widths = self.header.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |row: &Vec<String>| {
+            let cells = row.iter().zip(&widths).map(|(cell, width)| {
+                if cell.parse::<f64>().is_ok() {
+                    format!("{:>width$}", cell, width = width)
+                } else {
+                    format!("{:<width$}", cell, width = width)
+                }
+            });
+            cells.collect::<Vec<_>>().join(" | ").trim_end().to_string() + "\n"
+        };
+        let rule = widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("-+-");
+        let mut text = line(&self.header);
+        text.push_str(&rule);
+        text.push('\n');
+        for row in &self.rows {
+            text.push_str(&line(row));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faultloc_store::{Key, RegionRecord, Status, Tests};
+
+    use super::*;
+
+    const SOURCE: &str = "fn delete(k: i32) -> i32 {
+    /*| delete */
+    k - 1
+    /*|| delete_4 */
+    /*|
+    k
+    */
+    /* |*/
+}
+";
+
+    fn trial(
+        mutations: &[&str],
+        property: &str,
+        date: Option<&str>,
+        faulty: (usize, usize),
+    ) -> Trial {
+        let region = |line, ef, ep| RegionRecord {
+            file: "/home/w/BST/src/implementation.rs".to_string(),
+            start_line: line,
+            start_col: 5,
+            end_line: line,
+            end_col: 10,
+            function: Some("delete".to_string()),
+            ef,
+            ep,
+        };
+        Trial {
+            key: Key {
+                workload: "BST".to_string(),
+                mutations: mutations.iter().map(|m| m.to_string()).collect(),
+                strategy: "crabcheck".to_string(),
+                property: property.to_string(),
+                trial: 0,
+            },
+            status: Status::Finished,
+            seed: None,
+            tests: Tests { passed: 4, failed: 2, discarded: 0 },
+            rustc: String::new(),
+            rustflags: String::new(),
+            time: 0.1,
+            date: date.map(str::to_string),
+            output: String::new(),
+            // Line 6 lies in the block of delete_4, line 12 outside it.
+            regions: vec![region(6, faulty.0, faulty.1), region(12, 2, 1)],
+        }
+    }
+
+    fn trials() -> Vec<Trial> {
+        vec![
+            trial(&["delete_4"], "DeletePost", Some("2025-09-20T10:00:00Z"), (2, 0)),
+            trial(&["delete_4"], "DeletePost", Some("2025-09-21T10:00:00Z"), (2, 0)),
+            trial(&["delete_4"], "DeleteModel", Some("2025-09-22T10:00:00Z"), (1, 4)),
+            trial(&["delete_4", "insert_2"], "DeletePost", None, (2, 0)),
+        ]
+    }
+
+    #[test]
+    fn test_selection() {
+        let trials = trials();
+        let count = |selection: &Selection| trials.iter().filter(|t| selection.matches(t)).count();
+        assert_eq!(count(&Selection::default()), 4);
+        assert_eq!(count(&Selection { mutants: vec!["insert_2".into()], ..Default::default() }), 1);
+        assert_eq!(
+            count(&Selection { properties: vec!["DeleteModel".into()], ..Default::default() }),
+            1
+        );
+        let dated = Selection { since: Some("2025-09-21".into()), ..Default::default() };
+        assert_eq!(count(&dated), 2, "undated trials are left out");
+        let day = Selection { until: Some("2025-09-21".into()), ..dated };
+        assert_eq!(count(&day), 1);
+        assert!(date("2025-09-21").is_ok());
+        assert!(date("21-09-2025").is_err());
+    }
+
+    #[test]
+    fn test_queries() {
+        let groups = groups(&trials(), &Filter::default());
+        assert_eq!(groups.len(), 3);
+        assert_eq!((groups[1].property.as_str(), groups[1].trials), ("DeletePost", 2));
+        assert_eq!(groups[2].mutations, vec!["delete_4", "insert_2"]);
+
+        let top = top_regions(&groups, Formula::Ochiai, 1);
+        assert_eq!(top[1].ranking.len(), 1);
+        assert_eq!(top[1].ranking[0].region.start_line, 6);
+
+        let localizations = localize(&groups, Formula::Ochiai, |_, mutant| {
+            GroundTruth::from_source("BST/src/implementation.rs", SOURCE, mutant)
+        });
+        assert!(localizations.is_err(), "insert_2 is not in the source");
+        let localizations = localize(&groups[..2], Formula::Ochiai, |_, mutant| {
+            GroundTruth::from_source("BST/src/implementation.rs", SOURCE, mutant)
+        })
+        .unwrap();
+        let pivot = pivot(&localizations, Ties::Average);
+        assert_eq!(pivot.properties, vec!["DeleteModel", "DeletePost"]);
+        assert_eq!(pivot.rows.len(), 1);
+        assert_eq!(pivot.rows[0].mutant, "BST delete_4");
+        assert_eq!(pivot.rows[0].ranks, vec![Some(2.0), Some(1.0)]);
+        assert_eq!(pivot.rows[0].best.as_deref(), Some("DeletePost"));
+
+        let exam = exam(&localizations, Ties::Average);
+        assert_eq!(exam.len(), 1);
+        assert_eq!(exam[0].1.rankings, 2);
+        assert!((exam[0].1.mean_exam - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(&["mutant", "rank"]);
+        table.push(vec!["BST delete_4".to_string(), "1.5".to_string()]);
+        table.push(vec!["with, comma".to_string(), "-".to_string()]);
+        assert_eq!(table.csv(), "mutant,rank\nBST delete_4,1.5\n\"with, comma\",-\n");
+        assert_eq!(
+            table.aligned(),
+            "mutant       | rank\n-------------+-----\nBST delete_4 |  1.5\nwith, comma  | -\n"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::BTreeMap, fmt::Display, path::Path, str::FromStr};
+
+use faultloc_mutants::marauder::{self, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::{coverage::Region, formula::Ranked};
+
+/// The cut-offs reported by [`Score::top_n`] and [`Summary`].
+pub const TOP_N: [usize; 4] = [1, 3, 5, 10];
+
+/// Where the fault behind a marauder mutant lives.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GroundTruth {
+    pub mutant: String,
+    /// The source file as given, usually relative to the workload root.
+    pub file: String,
+    /// The whole marauder block that declares the mutant. Whichever variant is
+    /// live, its code lies inside this span.
+    pub span: Span,
+}
+
+impl GroundTruth {
+    pub fn from_source(file: &str, source: &str, mutant: &str) -> Result<GroundTruth, String> {
+        let blocks = marauder::parse(source).map_err(|e| format!("{}:{}", file, e))?;
+        let (block, _) = marauder::find_mutant(&blocks, mutant)
+            .ok_or_else(|| format!("No mutant named {} in {}", mutant, file))?;
+        Ok(GroundTruth { mutant: mutant.to_string(), file: file.to_string(), span: block.span })
+    }
+
+    pub fn from_file(file: &Path, mutant: &str) -> Result<GroundTruth, String> {
+        let source = std::fs::read_to_string(file)
+            .map_err(|e| format!("failed to read {}: {}", file.display(), e))?;
+        GroundTruth::from_source(&file.to_string_lossy(), &source, mutant)
+    }
+
+    /// A region is faulty when it shares at least one position with the block.
+    /// Coverage exports carry absolute paths, so files are matched by suffix.
+    pub fn contains(&self, region: &Region) -> bool {
+        let file = Path::new(&self.file);
+        let same_file = Path::new(&region.file).ends_with(file)
+            || file.ends_with(Path::new(&region.file))
+            || region.file == self.file;
+        same_file && self.span.intersects(&region.span())
+    }
+}
+
+/// How to rank a region that shares its score with others.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Ties {
+    /// The faulty region is inspected first among its ties.
+    Best,
+    /// The faulty region is inspected last among its ties.
+    Worst,
+    /// The mean of the best and worst case.
+    Average,
+}
+
+impl Display for Ties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ties::Best => write!(f, "best"),
+            Ties::Worst => write!(f, "worst"),
+            Ties::Average => write!(f, "average"),
+        }
+    }
+}
+
+impl FromStr for Ties {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "best" => Ok(Ties::Best),
+            "worst" => Ok(Ties::Worst),
+            "average" => Ok(Ties::Average),
+            _ => Err(format!("Unknown tie policy: {} (available: best, worst, average)", s)),
+        }
+    }
+}
+
+/// The 1-based position of the first faulty region, bounded by its ties.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FirstHit {
+    pub best: usize,
+    pub worst: usize,
+}
+
+impl FirstHit {
+    pub fn rank(&self, ties: Ties) -> f64 {
+        match ties {
+            Ties::Best => self.best as f64,
+            Ties::Worst => self.worst as f64,
+            Ties::Average => (self.best + self.worst) as f64 / 2.0,
+        }
+    }
+}
+
+/// How well a ranking localizes one mutant.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Score {
+    pub mutant: String,
+    pub regions: usize,
+    pub faulty: usize,
+    pub first_hit: Option<FirstHit>,
+}
+
+impl Score {
+    pub fn new(ranked: &[Ranked], truth: &GroundTruth) -> Score {
+        let faulty = ranked.iter().filter(|r| truth.contains(&r.region)).collect::<Vec<_>>();
+        let first_hit =
+            faulty.iter().map(|r| r.score).max_by(|a, b| a.total_cmp(b)).map(|score| FirstHit {
+                best: ranked.iter().filter(|r| r.score > score).count() + 1,
+                worst: ranked.iter().filter(|r| r.score >= score).count(),
+            });
+
+        Score {
+            mutant: truth.mutant.clone(),
+            regions: ranked.len(),
+            faulty: faulty.len(),
+            first_hit,
+        }
+    }
+
+    /// The fraction of regions inspected before reaching the fault. A ranking
+    /// that never reaches it scores 1.
+    pub fn exam(&self, ties: Ties) -> f64 {
+        match self.first_hit {
+            Some(hit) if self.regions > 0 => hit.rank(ties) / self.regions as f64,
+            _ => 1.0,
+        }
+    }
+
+    pub fn top_n(&self, n: usize, ties: Ties) -> bool {
+        self.first_hit.is_some_and(|hit| hit.rank(ties) <= n as f64)
+    }
+}
+
+/// Accumulated scores over many rankings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub ties: Ties,
+    pub rankings: usize,
+    pub localized: usize,
+    pub mean_exam: f64,
+    pub top_n: BTreeMap<usize, usize>,
+}
+
+impl Summary {
+    pub fn new(scores: &[Score], ties: Ties) -> Summary {
+        let rankings = scores.len();
+        let mean_exam = if rankings == 0 {
+            0.0
+        } else {
+            scores.iter().map(|s| s.exam(ties)).sum::<f64>() / rankings as f64
+        };
+        let top_n = TOP_N
+            .iter()
+            .map(|&n| (n, scores.iter().filter(|s| s.top_n(n, ties)).count()))
+            .collect();
+
+        Summary {
+            ties,
+            rankings,
+            localized: scores.iter().filter(|s| s.first_hit.is_some()).count(),
+            mean_exam,
+            top_n,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn insert(k: i32) -> i32 {
+    /*| insert */
+    k + 1
+    /*|| insert_1 */
+    /*|
+    k
+    */
+    /* |*/
+}
+";
+
+    fn ranked(line: u32, score: f64) -> Ranked {
+        Ranked {
+            rank: 0,
+            region: Region {
+                file: "/home/w/BST/src/implementation.rs".to_string(),
+                start_line: line,
+                start_col: 5,
+                end_line: line,
+                end_col: 10,
+            },
+            function: None,
+            ef: 0,
+            ep: 0,
+            nf: 0,
+            np: 0,
+            score,
+        }
+    }
+
+    fn truth() -> GroundTruth {
+        GroundTruth::from_source("src/implementation.rs", SOURCE, "insert_1").unwrap()
+    }
+
+    #[test]
+    fn test_ground_truth() {
+        let truth = truth();
+        assert_eq!((truth.span.start.line, truth.span.end.line), (2, 8));
+        assert!(truth.contains(&ranked(3, 0.0).region));
+        assert!(!truth.contains(&ranked(9, 0.0).region));
+        // A region ending right where the block starts stops short of it.
+        let mut before = ranked(1, 0.0);
+        (before.region.end_line, before.region.end_col) = (2, 5);
+        assert!(!truth.contains(&before.region));
+        before.region.end_col = 6;
+        assert!(truth.contains(&before.region));
+        let mut elsewhere = ranked(3, 0.0);
+        elsewhere.region.file = "/home/w/BST/src/spec.rs".to_string();
+        assert!(!truth.contains(&elsewhere.region));
+        assert!(GroundTruth::from_source("src/implementation.rs", SOURCE, "insert_9").is_err());
+    }
+
+    #[test]
+    fn test_score_ties() {
+        // The faulty region at line 3 ties with two others at rank 2 to 4.
+        let ranking = vec![
+            ranked(20, 0.9),
+            ranked(21, 0.5),
+            ranked(3, 0.5),
+            ranked(22, 0.5),
+            ranked(23, 0.1),
+        ];
+        let score = Score::new(&ranking, &truth());
+        assert_eq!(score.faulty, 1);
+        assert_eq!(score.first_hit, Some(FirstHit { best: 2, worst: 4 }));
+        assert_eq!(score.exam(Ties::Best), 0.4);
+        assert_eq!(score.exam(Ties::Worst), 0.8);
+        assert_eq!(score.exam(Ties::Average), 0.6);
+        assert!(!score.top_n(1, Ties::Best));
+        assert!(score.top_n(3, Ties::Average));
+        assert!(!score.top_n(3, Ties::Worst));
+    }
+
+    #[test]
+    fn test_summary() {
+        let hit = Score::new(&[ranked(3, 1.0), ranked(20, 0.5)], &truth());
+        let miss = Score::new(&[ranked(20, 1.0)], &truth());
+        assert_eq!(miss.first_hit, None);
+        assert_eq!(miss.exam(Ties::Best), 1.0);
+
+        let summary = Summary::new(&[hit, miss], Ties::Average);
+        assert_eq!(summary.rankings, 2);
+        assert_eq!(summary.localized, 1);
+        assert_eq!(summary.mean_exam, 0.75);
+        assert_eq!(summary.top_n[&1], 1);
+        assert_eq!(summary.top_n[&10], 1);
+    }
+}
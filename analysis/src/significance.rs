@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    coverage::{Region, RegionCounts, Spectrum},
+    formula::Formula,
+};
+
+/// A small deterministic generator (SplitMix64), so that a bootstrap can be
+/// repeated exactly from its seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform index below `n`, which must not be 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+/// How many bootstrap resamples to draw, and how wide an interval to report.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Options {
+    pub resamples: usize,
+    pub confidence: f64,
+    pub seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { resamples: 1000, confidence: 0.95, seed: 0 }
+    }
+}
+
+/// A region's suspiciousness over every trial, with how far it moves when
+/// the trials are resampled and how likely it is under random labels.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Assessed {
+    pub rank: usize,
+    pub region: Region,
+    pub function: Option<String>,
+    pub ef: usize,
+    pub ep: usize,
+    /// The score on all trials pooled together.
+    pub score: f64,
+    /// The bootstrap confidence interval of the score.
+    pub low: f64,
+    pub high: f64,
+    /// The bootstrap confidence interval of the rank, with tied regions
+    /// sharing the mean of the ranks they span.
+    pub rank_low: f64,
+    pub rank_high: f64,
+    /// The exact permutation-test p-value of the score: how likely a score at
+    /// least this high is if pass/fail labels are shuffled over executions.
+    pub p: f64,
+    /// The p-value adjusted for testing every region at once
+    /// (Benjamini-Hochberg), to compare against a false discovery rate.
+    pub q: f64,
+}
+
+/// Assesses every region of the trials, grouped into strata (one per
+/// property) that are resampled apart, so that each resample keeps the mix of
+/// properties. Regions are ordered by the lower bound of their interval, then
+/// by score and p-value, so that a region only outranks another on a score it
+/// keeps across resamples.
+pub fn assess(strata: &[Vec<Spectrum>], formula: Formula, options: Options) -> Vec<Assessed> {
+    let mut pooled = Spectrum::default();
+    for spectrum in strata.iter().flatten() {
+        pooled.merge(spectrum);
+    }
+    let regions = pooled.regions.keys().cloned().collect::<Vec<_>>();
+    let scores = |spectrum: &Spectrum| {
+        regions
+            .iter()
+            .map(|region| {
+                let counts = spectrum.regions.get(region).copied().unwrap_or_default();
+                formula.score(counts, spectrum.passed, spectrum.failed)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut rng = Rng::new(options.seed);
+    let mut sampled_scores = vec![vec![]; regions.len()];
+    let mut sampled_ranks = vec![vec![]; regions.len()];
+    for _ in 0..options.resamples {
+        let mut resample = Spectrum::default();
+        for stratum in strata.iter().filter(|stratum| !stratum.is_empty()) {
+            for _ in 0..stratum.len() {
+                resample.merge(&stratum[rng.below(stratum.len())]);
+            }
+        }
+        let scores = scores(&resample);
+        for (i, rank) in tied_ranks(&scores).into_iter().enumerate() {
+            sampled_scores[i].push(scores[i]);
+            sampled_ranks[i].push(rank);
+        }
+    }
+
+    let observed = scores(&pooled);
+    let ps = regions
+        .iter()
+        .map(|region| permutation_p(formula, pooled.regions[region], pooled.passed, pooled.failed))
+        .collect::<Vec<_>>();
+    let qs = adjust(&ps);
+    let ranks = tied_ranks(&observed);
+
+    let tail = (1.0 - options.confidence.clamp(0.0, 1.0)) / 2.0;
+    let mut assessed = regions
+        .iter()
+        .enumerate()
+        .map(|(i, region)| {
+            let counts = pooled.regions[region];
+            let (low, high) =
+                interval(&mut sampled_scores[i], tail).unwrap_or((observed[i], observed[i]));
+            let (rank_low, rank_high) =
+                interval(&mut sampled_ranks[i], tail).unwrap_or((ranks[i], ranks[i]));
+            Assessed {
+                rank: 0,
+                region: region.clone(),
+                function: pooled.functions.get(region).cloned(),
+                ef: counts.ef,
+                ep: counts.ep,
+                score: observed[i],
+                low,
+                high,
+                rank_low,
+                rank_high,
+                p: ps[i],
+                q: qs[i],
+            }
+        })
+        .collect::<Vec<_>>();
+
+    assessed.sort_by(|a, b| {
+        b.low.total_cmp(&a.low).then(b.score.total_cmp(&a.score)).then(a.p.total_cmp(&b.p))
+    });
+    for (i, a) in assessed.iter_mut().enumerate() {
+        a.rank = i + 1;
+    }
+    assessed
+}
+
+/// The ranks of `scores` from highest to lowest, with tied scores sharing the
+/// mean of the ranks they span.
+fn tied_ranks(scores: &[f64]) -> Vec<f64> {
+    let mut order = (0..scores.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+    let mut ranks = vec![0.0; scores.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && scores[order[end]] == scores[order[start]] {
+            end += 1;
+        }
+        // Ranks `start + 1..=end`, averaged.
+        let rank = (start + 1 + end) as f64 / 2.0;
+        for i in &order[start..end] {
+            ranks[*i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// The percentile interval that leaves `tail` of `samples` out on each side.
+fn interval(samples: &mut [f64], tail: f64) -> Option<(f64, f64)> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_by(f64::total_cmp);
+    let at = |q: f64| samples[(q * (samples.len() - 1) as f64).round() as usize];
+    Some((at(tail), at(1.0 - tail)))
+}
+
+/// The exact permutation-test p-value of a region's score. Shuffling the
+/// labels of `passed + failed` executions while keeping which of them cover
+/// the region makes the number of failing executions among the covering ones
+/// hypergeometric, so every relabeling is counted without sampling any.
+pub fn permutation_p(formula: Formula, counts: RegionCounts, passed: usize, failed: usize) -> f64 {
+    let covering = counts.ef + counts.ep;
+    if covering == 0 || passed == 0 || failed == 0 {
+        return 1.0;
+    }
+    let observed = formula.score(counts, passed, failed);
+    let lowest = covering.saturating_sub(passed);
+    let highest = covering.min(failed);
+
+    // Log-weights of each possible `ef`, up to a common factor, from the
+    // ratio of consecutive hypergeometric probabilities.
+    let mut weights = Vec::with_capacity(highest - lowest + 1);
+    let mut weight = 0.0;
+    for ef in lowest..=highest {
+        weights.push(weight);
+        weight += (((failed - ef) * (covering - ef)) as f64).ln()
+            - (((ef + 1) * (passed + ef + 1 - covering)) as f64).ln();
+    }
+    let max = weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let tolerance = 1e-12 * observed.abs().max(1.0);
+    let mut total = 0.0;
+    let mut extreme = 0.0;
+    for (ef, weight) in (lowest..=highest).zip(weights) {
+        let weight = (weight - max).exp();
+        total += weight;
+        let counts = RegionCounts { ef, ep: covering - ef };
+        if formula.score(counts, passed, failed) >= observed - tolerance {
+            extreme += weight;
+        }
+    }
+    (extreme / total).min(1.0)
+}
+
+/// Benjamini-Hochberg adjusted p-values, in the order of `ps`.
+fn adjust(ps: &[f64]) -> Vec<f64> {
+    let mut order = (0..ps.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| ps[*b].total_cmp(&ps[*a]));
+    let mut qs = vec![0.0; ps.len()];
+    let mut smallest = 1.0f64;
+    for (i, index) in order.into_iter().enumerate() {
+        // `ps.len() - i` is the index's rank from the smallest p-value.
+        let q = ps[index] * ps.len() as f64 / (ps.len() - i) as f64;
+        smallest = smallest.min(q);
+        qs[index] = smallest;
+    }
+    qs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::fixtures::spectrum;
+
+    #[test]
+    fn test_permutation_p() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        // 2 failing, 2 passing; a region covered by both failing executions
+        // alone is 1 relabeling in C(4, 2) = 6.
+        let p = permutation_p(Formula::Ochiai, RegionCounts { ef: 2, ep: 0 }, 2, 2);
+        assert!(close(p, 1.0 / 6.0), "{}", p);
+        assert_eq!(permutation_p(Formula::Ochiai, RegionCounts { ef: 2, ep: 2 }, 2, 2), 1.0);
+        assert_eq!(permutation_p(Formula::Ochiai, RegionCounts { ef: 0, ep: 0 }, 2, 2), 1.0);
+        assert_eq!(tied_ranks(&[0.5, 1.0, 0.5, 0.0]), vec![2.5, 1.0, 2.5, 4.0]);
+        let qs = adjust(&[0.01, 0.04, 0.03]);
+        assert!(close(qs[0], 0.03) && close(qs[1], 0.04) && close(qs[2], 0.04), "{:?}", qs);
+    }
+
+    #[test]
+    fn test_assess() {
+        // Region 1 is covered by the failing executions of every trial,
+        // region 2 only in one, and region 3 by everything.
+        let strata = vec![vec![
+            spectrum(10, 2, &[(1, 2, 0), (2, 2, 0), (3, 2, 10)]),
+            spectrum(10, 2, &[(1, 2, 0), (2, 0, 5), (3, 2, 10)]),
+            spectrum(10, 2, &[(1, 2, 1), (2, 0, 5), (3, 2, 10)]),
+        ]];
+        let options = Options { resamples: 200, ..Default::default() };
+        let assessed = assess(&strata, Formula::Ochiai, options);
+        let lines = assessed.iter().map(|a| a.region.start_line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 3, 2]);
+        let first = &assessed[0];
+        assert!(first.low <= first.score && first.score <= first.high);
+        assert_eq!(first.rank_low, 1.0);
+        assert!(first.p < 0.001 && first.q < 0.001);
+        // Region 3 is covered by every execution, which no relabeling changes.
+        assert_eq!(assessed[1].p, 1.0);
+        assert!(assessed[2].high > assessed[2].low);
+
+        assert_eq!(assess(&strata, Formula::Ochiai, options), assessed);
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    coverage::{Outcome, RegionCounts},
+    formula::Formula,
+};
+
+/// One call of a traced function, as `faultloc --trace` writes it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub function: String,
+    /// How many traced calls were running when this one was entered.
+    pub depth: usize,
+    #[serde(default)]
+    pub args: String,
+    pub ret: Option<String>,
+}
+
+/// How often a function was entered and exited.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CallCounts {
+    pub enter: usize,
+    pub exit: usize,
+}
+
+/// The calls of a single traced test, in the order they were entered.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub id: String,
+    pub outcome: Outcome,
+    pub calls: Vec<Call>,
+    pub counts: BTreeMap<String, CallCounts>,
+}
+
+#[derive(Deserialize)]
+struct Export {
+    calls: Vec<Call>,
+    #[serde(default)]
+    counts: BTreeMap<String, CallCounts>,
+}
+
+impl Trace {
+    pub fn from_json(id: String, outcome: Outcome, json: &str) -> Result<Trace, String> {
+        let export: Export =
+            serde_json::from_str(json).map_err(|e| format!("{}: invalid trace: {}", id, e))?;
+        Ok(Trace { id, outcome, calls: export.calls, counts: export.counts })
+    }
+
+    /// The functions called, in the order they were entered.
+    pub fn sequence(&self) -> Vec<&str> {
+        self.calls.iter().map(|call| call.function.as_str()).collect()
+    }
+
+    /// Every run of `n` consecutive calls in [`Trace::sequence`].
+    pub fn ngrams(&self, n: usize) -> BTreeSet<Vec<String>> {
+        let sequence = self.sequence();
+        sequence
+            .windows(n.max(1))
+            .map(|window| window.iter().map(|f| f.to_string()).collect())
+            .collect()
+    }
+}
+
+/// Loads every `trace_*.json` under `dir` whose name carries an outcome tag.
+pub fn load_dir(dir: &Path) -> Result<Vec<Trace>, String> {
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "json")
+                && path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("trace_"))
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    let mut traces = Vec::with_capacity(paths.len());
+    for path in paths {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let Some(outcome) = Outcome::from_stem(&stem) else {
+            eprintln!("Skipping {}: no pass/fail/discard tag in its name", path.display());
+            continue;
+        };
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        traces.push(Trace::from_json(stem, outcome, &json)?);
+    }
+    Ok(traces)
+}
+
+/// The enter and exit counts of a function, summed over failing and passing
+/// traces apart.
+#[derive(Serialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FunctionCounts {
+    pub failing: CallCounts,
+    pub passing: CallCounts,
+}
+
+/// Pass/fail counts per call n-gram over a set of traces: an n-gram is
+/// covered by a trace that makes those `n` calls one after the other.
+/// Discarded traces are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceSpectrum {
+    pub n: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub ngrams: BTreeMap<Vec<String>, RegionCounts>,
+    pub functions: BTreeMap<String, FunctionCounts>,
+}
+
+impl SequenceSpectrum {
+    pub fn from_traces(traces: &[Trace], n: usize) -> SequenceSpectrum {
+        let mut spectrum = SequenceSpectrum { n, ..Default::default() };
+        for trace in traces {
+            let failing = match trace.outcome {
+                Outcome::Pass => false,
+                Outcome::Fail => true,
+                Outcome::Discard => continue,
+            };
+            if failing {
+                spectrum.failed += 1;
+            } else {
+                spectrum.passed += 1;
+            }
+            for ngram in trace.ngrams(n) {
+                let counts = spectrum.ngrams.entry(ngram).or_default();
+                if failing {
+                    counts.ef += 1;
+                } else {
+                    counts.ep += 1;
+                }
+            }
+            for (function, calls) in &trace.counts {
+                let counts = spectrum.functions.entry(function.clone()).or_default();
+                let side = if failing { &mut counts.failing } else { &mut counts.passing };
+                side.enter += calls.enter;
+                side.exit += calls.exit;
+            }
+        }
+        spectrum
+    }
+}
+
+/// An n-gram of the spectrum with its counts and suspiciousness score.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RankedNgram {
+    pub rank: usize,
+    pub ngram: Vec<String>,
+    pub ef: usize,
+    pub ep: usize,
+    pub nf: usize,
+    pub np: usize,
+    pub score: f64,
+}
+
+/// Ranks every n-gram of the spectrum from most to least suspicious. Ties
+/// keep lexicographic order, so the ranking is deterministic.
+pub fn rank(spectrum: &SequenceSpectrum, formula: Formula) -> Vec<RankedNgram> {
+    let mut ranked = spectrum
+        .ngrams
+        .iter()
+        .map(|(ngram, counts)| RankedNgram {
+            rank: 0,
+            ngram: ngram.clone(),
+            ef: counts.ef,
+            ep: counts.ep,
+            nf: spectrum.failed - counts.ef,
+            np: spectrum.passed - counts.ep,
+            score: formula.score(*counts, spectrum.passed, spectrum.failed),
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    for (i, r) in ranked.iter_mut().enumerate() {
+        r.rank = i + 1;
+    }
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(id: &str, outcome: Outcome, functions: &[&str]) -> Trace {
+        let calls = functions
+            .iter()
+            .enumerate()
+            .map(|(depth, f)| format!(r#"{{"function":"{}","depth":{},"ret":null}}"#, f, depth))
+            .collect::<Vec<_>>();
+        let json = format!(
+            r#"{{"calls":[{}],"counts":{{"ins":{{"enter":2,"exit":2}}}}}}"#,
+            calls.join(",")
+        );
+        Trace::from_json(id.to_string(), outcome, &json).unwrap()
+    }
+
+    #[test]
+    fn test_sequence_spectrum() {
+        let traces = vec![
+            trace("a", Outcome::Fail, &["insert", "ins", "ins", "balance"]),
+            trace("b", Outcome::Pass, &["insert", "ins", "balance"]),
+            trace("c", Outcome::Pass, &["insert", "ins"]),
+            trace("d", Outcome::Discard, &["insert", "ins", "ins"]),
+        ];
+        assert_eq!(traces[0].ngrams(2).len(), 3);
+
+        let spectrum = SequenceSpectrum::from_traces(&traces, 2);
+        assert_eq!((spectrum.passed, spectrum.failed), (2, 1));
+        let key = |ngram: &[&str]| ngram.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        assert_eq!(spectrum.ngrams[&key(&["insert", "ins"])], RegionCounts { ef: 1, ep: 2 });
+        assert_eq!(spectrum.ngrams[&key(&["ins", "ins"])], RegionCounts { ef: 1, ep: 0 });
+        let ins = spectrum.functions["ins"];
+        assert_eq!((ins.failing.enter, ins.passing.exit), (2, 4));
+
+        let ranked = rank(&spectrum, Formula::Ochiai);
+        assert_eq!(ranked[0].ngram, key(&["ins", "ins"]));
+        assert_eq!(ranked.last().unwrap().ngram, key(&["insert", "ins"]));
+    }
+}
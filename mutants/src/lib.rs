@@ -0,0 +1,4 @@
+pub mod marauder;
+pub mod operators;
+pub mod schemata;
+pub mod switch;
@@ -0,0 +1,461 @@
+//! Parser for the marauder mutation comments used in `implementation.rs`:
+//!
+//! ```text
+//! /*| insert */
+//! base code
+//! /*|| insert_1 */
+//! /*|
+//! mutant code
+//! */
+//! /* |*/
+//! ```
+//!
+//! Every alternative of a block, the base included, is a [`Variant`]. At most
+//! one of them is live code; the others sit inside `/*| ... */` comments. The
+//! parser does not assume that the live one is the base, so it reads files in
+//! which a mutant has been switched on just as well.
+
+use std::{fmt::Display, ops::Range};
+
+use serde::{Deserialize, Serialize};
+
+/// A 1-based line and column, as reported by `llvm-cov`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// An inclusive range of source positions.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn intersects(&self, other: &Span) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} - {}:{}", self.start.line, self.start.col, self.end.line, self.end.col)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    /// `None` for the base code right after the block header.
+    pub name: Option<String>,
+    /// Whether this alternative is live code rather than commented out.
+    pub active: bool,
+    /// The code of the alternative, without the surrounding markers.
+    pub text: String,
+    pub span: Span,
+    /// Byte range of `text` in the source.
+    pub range: Range<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// The name after `/*|`, empty for anonymous blocks.
+    pub name: String,
+    /// From the opening `/*|` to the closing `|*/`.
+    pub span: Span,
+    pub range: Range<usize>,
+    pub variants: Vec<Variant>,
+}
+
+impl Block {
+    pub fn base(&self) -> &Variant {
+        &self.variants[0]
+    }
+
+    pub fn mutants(&self) -> impl Iterator<Item = &Variant> {
+        self.variants[1..].iter()
+    }
+
+    pub fn mutant(&self, name: &str) -> Option<&Variant> {
+        self.mutants().find(|variant| variant.name.as_deref() == Some(name))
+    }
+
+    pub fn active(&self) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.active)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: Position,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.position.line, self.position.col, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// `/*| name */`
+    Open(String),
+    /// `/*|| name */`
+    Mutant(String),
+    /// `/*|` at the end of a line, closed by the next `*/`.
+    Commented(Range<usize>),
+    /// `/* |*/`
+    Close,
+}
+
+/// A lexing failure: the byte offset it happened at and what went wrong.
+type LexError = (usize, String);
+
+struct Lexer<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a> Lexer<'a> {
+    /// Returns the next marker with the byte range it covers.
+    fn next(&mut self) -> Result<Option<(Token, Range<usize>)>, LexError> {
+        loop {
+            let Some(found) = self.source[self.offset..].find("/*") else {
+                self.offset = self.source.len();
+                return Ok(None);
+            };
+            let start = self.offset + found;
+            let rest = &self.source[start..];
+
+            if rest.starts_with("/* |*/") {
+                self.offset = start + "/* |*/".len();
+                return Ok(Some((Token::Close, start..self.offset)));
+            }
+            if let Some(header) = rest.strip_prefix("/*||") {
+                let (name, len) = Self::header(header)
+                    .ok_or((start, "unterminated mutant header".to_string()))?;
+                self.offset = start + "/*||".len() + len;
+                return Ok(Some((Token::Mutant(name), start..self.offset)));
+            }
+            if let Some(header) = rest.strip_prefix("/*|") {
+                let line = header.split('\n').next().unwrap_or_default();
+                if line.trim().is_empty() {
+                    let body = start + "/*|".len();
+                    let end = self.source[body..]
+                        .find("*/")
+                        .map(|end| body + end)
+                        .ok_or((start, "unterminated commented variant".to_string()))?;
+                    self.offset = end + "*/".len();
+                    return Ok(Some((Token::Commented(body..end), start..self.offset)));
+                }
+                let (name, len) =
+                    Self::header(header).ok_or((start, "unterminated block header".to_string()))?;
+                self.offset = start + "/*|".len() + len;
+                return Ok(Some((Token::Open(name), start..self.offset)));
+            }
+
+            // An ordinary comment, skip it whole.
+            self.offset = match rest.find("*/") {
+                Some(end) => start + end + "*/".len(),
+                None => self.source.len(),
+            };
+        }
+    }
+
+    /// Reads ` name */` on the current line and returns the name and length.
+    fn header(rest: &str) -> Option<(String, usize)> {
+        let end = rest.find("*/")?;
+        let name = &rest[..end];
+        if name.contains('\n') {
+            return None;
+        }
+        Some((name.trim().to_string(), end + "*/".len()))
+    }
+}
+
+fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() as u32 + 1;
+    let col = before.rfind('\n').map(|newline| offset - newline).unwrap_or(offset + 1) as u32;
+    Position { line, col }
+}
+
+/// The span of `range` after trimming surrounding whitespace. An all
+/// whitespace range collapses onto its start.
+fn trimmed(source: &str, range: Range<usize>) -> (Span, Range<usize>) {
+    let text = &source[range.clone()];
+    let start = range.start + (text.len() - text.trim_start().len());
+    let end = (range.end - (text.len() - text.trim_end().len())).max(start);
+    let span = Span {
+        start: position(source, start),
+        end: position(source, if end > start { end - 1 } else { start }),
+    };
+    (span, start..end)
+}
+
+fn variant(source: &str, name: Option<String>, code: Range<usize>, active: bool) -> Variant {
+    let (span, range) = trimmed(source, code);
+    Variant { name, active, text: source[range.clone()].to_string(), span, range }
+}
+
+/// Parses every marauder block in `source`.
+pub fn parse(source: &str) -> Result<Vec<Block>, ParseError> {
+    let error = |offset: usize, message: &str| ParseError {
+        position: position(source, offset),
+        message: message.to_string(),
+    };
+
+    let mut lexer = Lexer { source, offset: 0 };
+    let mut next = || lexer.next().map_err(|(offset, message)| error(offset, &message));
+    let mut blocks = vec![];
+
+    while let Some((token, open)) = next()? {
+        let name = match token {
+            Token::Open(name) => name,
+            _ => return Err(error(open.start, "marker outside of a block")),
+        };
+
+        let mut variants = vec![];
+        let mut current: Option<String> = None;
+        let mut code_start = open.end;
+        let mut commented = None;
+        let close = loop {
+            let Some((token, range)) = next()? else {
+                return Err(error(open.start, "block is never closed"));
+            };
+            match token {
+                Token::Commented(body) => {
+                    if commented.is_some() || !source[code_start..range.start].trim().is_empty() {
+                        return Err(error(range.start, "variant has both live and commented code"));
+                    }
+                    commented = Some(body);
+                },
+                Token::Mutant(_) | Token::Close => {
+                    variants.push(match commented.take() {
+                        Some(body) => variant(source, current.take(), body, false),
+                        None => variant(source, current.take(), code_start..range.start, true),
+                    });
+                    match token {
+                        Token::Mutant(name) => current = Some(name),
+                        _ => break range,
+                    }
+                },
+                Token::Open(_) => {
+                    return Err(error(range.start, "nested blocks are not supported"));
+                },
+            }
+            code_start = range.end;
+        };
+
+        if variants.iter().filter(|variant| variant.active).count() > 1 {
+            return Err(error(open.start, "more than one live variant in block"));
+        }
+
+        blocks.push(Block {
+            name,
+            span: Span {
+                start: position(source, open.start),
+                end: position(source, close.end - 1),
+            },
+            range: open.start..close.end,
+            variants,
+        });
+    }
+
+    Ok(blocks)
+}
+
+/// Finds the block declaring the mutant `name`.
+pub fn find_mutant<'a>(blocks: &'a [Block], name: &str) -> Option<(&'a Block, &'a Variant)> {
+    blocks.iter().find_map(|block| block.mutant(name).map(|variant| (block, variant)))
+}
+
+/// The `/*|` ... `*/` comment around a commented variant.
+fn comment(source: &str, variant: &Variant) -> Range<usize> {
+    let start = source[..variant.range.start].rfind("/*|").unwrap_or(variant.range.start);
+    let end = source[variant.range.end..]
+        .find("*/")
+        .map(|end| variant.range.end + end + "*/".len())
+        .unwrap_or(variant.range.end);
+    start..end
+}
+
+/// The edits that make `variant` the live code of `block`, commenting out
+/// whatever the block ran before.
+fn switch_edits(source: &str, block: &Block, variant: &Variant) -> Vec<(Range<usize>, String)> {
+    if variant.active {
+        return vec![];
+    }
+    let mut edits = vec![];
+    if let Some(live) = block.active() {
+        edits.push((live.range.clone(), format!("/*|\n{}\n*/", live.text)));
+    }
+    edits.push((comment(source, variant), variant.text.clone()));
+    edits
+}
+
+fn splice(source: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut applied = String::with_capacity(source.len());
+    let mut last = 0;
+    for (range, text) in edits {
+        applied.push_str(&source[last..range.start]);
+        applied.push_str(&text);
+        last = range.end;
+    }
+    applied.push_str(&source[last..]);
+    applied
+}
+
+/// Rewrites `source` so that `variant` of `block`, which must have been
+/// parsed from it, is live. Unlike [`apply`], this can switch a block back
+/// to its base code.
+pub fn switch(source: &str, block: &Block, variant: &Variant) -> String {
+    splice(source, switch_edits(source, block, variant))
+}
+
+/// Rewrites `source` so that the given mutants are live, commenting out
+/// whatever their blocks ran before. Other blocks are left as they are.
+pub fn apply(source: &str, mutants: &[&str]) -> Result<String, ParseError> {
+    let blocks = parse(source)?;
+    let mut edits: Vec<(Range<usize>, String)> = vec![];
+    for name in mutants {
+        let (block, variant) = find_mutant(&blocks, name).ok_or_else(|| ParseError {
+            position: Position { line: 1, col: 1 },
+            message: format!("no mutant named {}", name),
+        })?;
+        if mutants.iter().filter(|other| block.mutant(other).is_some()).count() > 1 {
+            return Err(ParseError {
+                position: block.span.start,
+                message: format!("mutant {} shares its block with another selected mutant", name),
+            });
+        }
+        edits.extend(switch_edits(source, block, variant));
+    }
+    Ok(splice(source, edits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn insert(k: i32) -> i32 {
+    /*| insert */
+    k + 1
+    /*|| insert_1 */
+    /*|
+    k
+    */
+    /*|| insert_2 */
+    /*|
+    k + 2
+    */
+    /* |*/
+}
+
+fn balance(c: Color) -> Color {
+    match c {
+        /*| */
+        R => B,
+        /*|| swap_cd */
+        /*|
+        R => R,
+        */
+        /* |*/
+        // Plain comments are left alone.
+        B => B,
+    }
+}
+";
+
+    #[test]
+    fn test_parse_blocks() {
+        let blocks = parse(SOURCE).unwrap();
+        assert_eq!(blocks.len(), 2);
+
+        let insert = &blocks[0];
+        assert_eq!(insert.name, "insert");
+        assert_eq!(insert.span.start, Position { line: 2, col: 5 });
+        assert_eq!(insert.span.end, Position { line: 12, col: 10 });
+        assert_eq!(insert.base().text, "k + 1");
+        assert_eq!(
+            insert.base().span,
+            Span { start: Position { line: 3, col: 5 }, end: Position { line: 3, col: 9 } }
+        );
+        assert_eq!(insert.active(), Some(insert.base()));
+        let names = insert.mutants().map(|v| v.name.clone().unwrap()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["insert_1", "insert_2"]);
+        assert_eq!(insert.mutant("insert_2").unwrap().text, "k + 2");
+        assert_eq!(insert.mutant("insert_2").unwrap().span.start.line, 10);
+        assert_eq!(&SOURCE[insert.mutant("insert_1").unwrap().range.clone()], "k");
+
+        let balance = &blocks[1];
+        assert_eq!(balance.name, "");
+        assert_eq!(balance.base().text, "R => B,");
+        assert_eq!(balance.mutant("swap_cd").unwrap().text, "R => R,");
+    }
+
+    #[test]
+    fn test_parse_switched_on_mutant() {
+        let source = "/*| insert */\n/*|\nk + 1\n*/\n/*|| insert_1 */\nk\n/* |*/\n";
+        let blocks = parse(source).unwrap();
+        assert!(!blocks[0].base().active);
+        assert_eq!(blocks[0].active().unwrap().name.as_deref(), Some("insert_1"));
+    }
+
+    #[test]
+    fn test_find_mutant() {
+        let blocks = parse(SOURCE).unwrap();
+        let (block, variant) = find_mutant(&blocks, "swap_cd").unwrap();
+        assert_eq!(block.span.start.line, 17);
+        assert_eq!(variant.span.start.line, 21);
+        assert!(find_mutant(&blocks, "delete_4").is_none());
+    }
+
+    #[test]
+    fn test_apply() {
+        let applied = apply(SOURCE, &["insert_2", "swap_cd"]).unwrap();
+        let blocks = parse(&applied).unwrap();
+        assert_eq!(blocks[0].active().unwrap().name.as_deref(), Some("insert_2"));
+        assert_eq!(blocks[0].base().text, "k + 1");
+        assert_eq!(blocks[1].active().unwrap().text, "R => R,");
+
+        // Switching back to a mutant of the same block is a no-op.
+        assert_eq!(apply(&applied, &["insert_2"]).unwrap(), applied);
+        assert!(apply(SOURCE, &["insert_1", "insert_2"]).is_err());
+        assert!(apply(SOURCE, &["delete_4"]).is_err());
+    }
+
+    #[test]
+    fn test_switch_back_to_base() {
+        let applied = apply(SOURCE, &["insert_2"]).unwrap();
+        let blocks = parse(&applied).unwrap();
+        let restored = switch(&applied, &blocks[0], blocks[0].base());
+        let restored = parse(&restored).unwrap();
+        assert_eq!(restored[0].active(), Some(restored[0].base()));
+        assert_eq!(restored[0].base().text, "k + 1");
+        assert_eq!(restored[0].mutant("insert_2").unwrap().text, "k + 2");
+        assert!(!restored[0].mutant("insert_2").unwrap().active);
+        assert_eq!(switch(&applied, &blocks[1], blocks[1].base()), applied);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("/*| insert */\nk + 1\n").is_err());
+        assert!(parse("/*|| insert_1 */\n").is_err());
+        assert!(parse("/*| a */\nx\n/*|| b */\ny\n/* |*/\n").is_err());
+    }
+
+    #[test]
+    fn test_span_intersects() {
+        let span =
+            |a, b| Span { start: Position { line: a, col: 1 }, end: Position { line: b, col: 1 } };
+        assert!(span(1, 5).intersects(&span(5, 9)));
+        assert!(span(3, 4).intersects(&span(1, 9)));
+        assert!(!span(1, 4).intersects(&span(5, 9)));
+    }
+}
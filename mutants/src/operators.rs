@@ -0,0 +1,477 @@
+//! Mutation operators for mutation-based fault localization.
+//!
+//! Where marauder mutants are written by hand to seed a fault, these are
+//! generated from the live code of a source file, so that running the
+//! properties against each of them tells which locations change what passes
+//! and what fails. Generated on top of a source with seeded mutants switched
+//! on, they are second-order mutants of the original.
+//!
+//! Operators only touch the code syn sees, so commented-out marauder variants
+//! and `#[cfg(test)]` modules are never mutated. The types they rely on, the
+//! `Box` fields of a variant or the variants of a field-less enum, are read
+//! from the enums declared in the same file.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    ops::Range,
+    str::FromStr,
+};
+
+use proc_macro2::{LineColumn, Span as TokenSpan};
+use serde::{Deserialize, Serialize};
+use syn::{
+    BinOp, Expr, ExprCall, Fields, FnArg, ImplItemFn, ItemEnum, ItemFn, ItemMod, ReturnType,
+    Signature,
+    spanned::Spanned,
+    visit::{self, Visit},
+};
+
+use crate::marauder::{Position, Span};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Operator {
+    /// Negates a comparison: `<` into `>=`, `==` into `!=`, and so on.
+    FlipComparison,
+    /// Swaps the first and last `Box` arguments of a variant, such as the
+    /// children of a tree node.
+    SwapChildren,
+    /// Replaces a variant of a field-less enum, such as a `Color`, with
+    /// another.
+    ChangeVariant,
+    /// Replaces a recursive call with one of its arguments of the return
+    /// type, as if the recursion stopped there.
+    DropCall,
+}
+
+impl Operator {
+    pub const ALL: [Operator; 4] = [
+        Operator::FlipComparison,
+        Operator::SwapChildren,
+        Operator::ChangeVariant,
+        Operator::DropCall,
+    ];
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operator::FlipComparison => write!(f, "flip-comparison"),
+            Operator::SwapChildren => write!(f, "swap-children"),
+            Operator::ChangeVariant => write!(f, "change-variant"),
+            Operator::DropCall => write!(f, "drop-call"),
+        }
+    }
+}
+
+impl FromStr for Operator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Operator::ALL.into_iter().find(|operator| operator.to_string() == s).ok_or_else(|| {
+            format!(
+                "Unknown operator: {} (available: flip-comparison, swap-children, change-variant, drop-call)",
+                s
+            )
+        })
+    }
+}
+
+/// One rewrite of a source file: `original`, at `range`, becomes
+/// `replacement`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mutation {
+    pub operator: Operator,
+    /// The innermost function the mutated code lies in.
+    pub function: Option<String>,
+    /// Where `original` lies, in the positions `llvm-cov` reports.
+    pub span: Span,
+    /// The byte offsets of `original`.
+    pub range: Range<usize>,
+    pub original: String,
+    pub replacement: String,
+}
+
+impl Mutation {
+    /// `source` with the mutation applied. `source` must be the text the
+    /// mutation was generated from.
+    pub fn apply(&self, source: &str) -> String {
+        let mut mutated = String::with_capacity(source.len() + self.replacement.len());
+        mutated.push_str(&source[..self.range.start]);
+        mutated.push_str(&self.replacement);
+        mutated.push_str(&source[self.range.end..]);
+        mutated
+    }
+}
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}: {} => {}", self.operator, self.span, self.original, self.replacement)
+    }
+}
+
+/// Every mutation of the live code of `source`, in source order.
+pub fn generate(source: &str) -> Result<Vec<Mutation>, String> {
+    let file = syn::parse_file(source).map_err(|e| {
+        let start = e.span().start();
+        format!("{}:{}: {}", start.line, start.column + 1, e)
+    })?;
+
+    let mut enums = Enums::default();
+    enums.visit_file(&file);
+    let mut generator =
+        Generator { text: Text::new(source), enums, functions: vec![], found: vec![] };
+    generator.visit_file(&file);
+
+    let mut mutations = generator.found;
+    mutations.sort_by(|a, b| {
+        (a.range.start, a.range.end, a.operator, &a.replacement).cmp(&(
+            b.range.start,
+            b.range.end,
+            b.operator,
+            &b.replacement,
+        ))
+    });
+    mutations.dedup();
+    Ok(mutations)
+}
+
+/// Maps the line and column positions syn reports to byte offsets.
+struct Text<'a> {
+    source: &'a str,
+    lines: Vec<usize>,
+}
+
+impl<'a> Text<'a> {
+    fn new(source: &'a str) -> Text<'a> {
+        let lines = std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1));
+        Text { source, lines: lines.collect() }
+    }
+
+    /// The byte offset of a 1-based line and a 0-based column in characters.
+    fn offset(&self, at: LineColumn) -> usize {
+        let start = self.lines[at.line - 1];
+        self.source[start..]
+            .char_indices()
+            .nth(at.column)
+            .map(|(i, _)| start + i)
+            .unwrap_or(self.source.len())
+    }
+
+    fn range(&self, span: TokenSpan) -> Range<usize> {
+        self.offset(span.start())..self.offset(span.end())
+    }
+
+    /// The inclusive `llvm-cov` positions of `range`.
+    fn span(&self, range: &Range<usize>) -> Span {
+        let position = |offset: usize| {
+            let line = self.lines.partition_point(|start| *start <= offset);
+            let col = self.source[self.lines[line - 1]..offset].chars().count() + 1;
+            Position { line: line as u32, col: col as u32 }
+        };
+        let last = self.source[..range.end].char_indices().next_back().map(|(i, _)| i);
+        Span { start: position(range.start), end: position(last.unwrap_or(range.start)) }
+    }
+}
+
+fn is_test(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let path = attr.path();
+        path.is_ident("test")
+            || (path.is_ident("cfg")
+                && attr.parse_args::<syn::Ident>().is_ok_and(|ident| ident == "test"))
+    })
+}
+
+/// Tokens as text, without the spacing `to_token_stream` puts between them.
+fn tokens(tokens: &impl quote::ToTokens) -> String {
+    tokens.to_token_stream().to_string().split_whitespace().collect()
+}
+
+/// What the operators need to know of the enums of a file.
+#[derive(Default)]
+struct Enums {
+    /// The indices of the `Box` fields of each tuple variant.
+    boxes: BTreeMap<String, Vec<usize>>,
+    /// The variants of each enum whose variants have no fields, by variant.
+    units: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Visit<'_> for Enums {
+    fn visit_item_enum(&mut self, item: &ItemEnum) {
+        for variant in &item.variants {
+            if let Fields::Unnamed(fields) = &variant.fields {
+                let boxes = fields
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, field)| tokens(&field.ty).starts_with("Box<"))
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                self.boxes.insert(variant.ident.to_string(), boxes);
+            }
+        }
+        if item.variants.len() > 1
+            && item.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit))
+        {
+            let variants = item
+                .variants
+                .iter()
+                .map(|variant| variant.ident.to_string())
+                .collect::<BTreeSet<_>>();
+            for variant in &variants {
+                self.units.insert(variant.clone(), variants.clone());
+            }
+        }
+    }
+
+    fn visit_item_mod(&mut self, item: &ItemMod) {
+        if !is_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+}
+
+/// The name and types of a function the generator is inside of.
+struct Function {
+    name: String,
+    /// The type of each argument, as text.
+    inputs: Vec<String>,
+    output: String,
+}
+
+impl Function {
+    fn new(signature: &Signature) -> Function {
+        let inputs = signature
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Receiver(receiver) => tokens(&receiver.ty),
+                FnArg::Typed(typed) => tokens(&typed.ty),
+            })
+            .collect();
+        let output = match &signature.output {
+            ReturnType::Default => "()".to_string(),
+            ReturnType::Type(_, ty) => tokens(ty),
+        };
+        Function { name: signature.ident.to_string(), inputs, output }
+    }
+}
+
+struct Generator<'a> {
+    text: Text<'a>,
+    enums: Enums,
+    functions: Vec<Function>,
+    found: Vec<Mutation>,
+}
+
+impl Generator<'_> {
+    fn push(&mut self, operator: Operator, range: Range<usize>, replacement: String) {
+        let original = self.text.source[range.clone()].to_string();
+        if original == replacement {
+            return;
+        }
+        self.found.push(Mutation {
+            operator,
+            function: self.functions.last().map(|function| function.name.clone()),
+            span: self.text.span(&range),
+            range,
+            original,
+            replacement,
+        });
+    }
+
+    fn flip_comparison(&mut self, op: &BinOp) {
+        let negated = match op {
+            BinOp::Lt(_) => ">=",
+            BinOp::Le(_) => ">",
+            BinOp::Gt(_) => "<=",
+            BinOp::Ge(_) => "<",
+            BinOp::Eq(_) => "!=",
+            BinOp::Ne(_) => "==",
+            _ => return,
+        };
+        self.push(Operator::FlipComparison, self.text.range(op.span()), negated.to_string());
+    }
+
+    fn swap_children(&mut self, call: &ExprCall, variant: &str) {
+        let Some(boxes) = self.enums.boxes.get(variant) else { return };
+        let (Some(&first), Some(&last)) = (boxes.first(), boxes.last()) else { return };
+        if first == last || call.args.len() <= last {
+            return;
+        }
+        let first = self.text.range(call.args[first].span());
+        let last = self.text.range(call.args[last].span());
+        let source = self.text.source;
+        let swapped = format!(
+            "{}{}{}",
+            &source[last.clone()],
+            &source[first.end..last.start],
+            &source[first.clone()]
+        );
+        self.push(Operator::SwapChildren, first.start..last.end, swapped);
+    }
+
+    fn drop_call(&mut self, call: &ExprCall) {
+        let Some(function) = self.functions.last() else { return };
+        if function.inputs.len() != call.args.len() {
+            return;
+        }
+        // The argument itself when it has the return type, wrapped or cloned
+        // into it when that is all it takes.
+        let output = function.output.clone();
+        let some = output.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>'));
+        let replacements = function
+            .inputs
+            .iter()
+            .zip(&call.args)
+            .filter_map(|(input, arg)| {
+                let text = &self.text.source[self.text.range(arg.span())];
+                if *input == output {
+                    Some(text.to_string())
+                } else if some == Some(input.as_str()) {
+                    Some(format!("Some({})", text))
+                } else if input.strip_prefix('&') == Some(output.as_str()) {
+                    Some(format!("({}).clone()", text))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let range = self.text.range(call.span());
+        for replacement in replacements {
+            self.push(Operator::DropCall, range.clone(), replacement);
+        }
+    }
+
+    fn change_variant(&mut self, expr: &syn::ExprPath) {
+        let segments = &expr.path.segments;
+        let Some(last) = segments.last() else { return };
+        if expr.qself.is_some() || segments.len() > 2 || !last.arguments.is_none() {
+            return;
+        }
+        let Some(variants) = self.enums.units.get(&last.ident.to_string()).cloned() else {
+            return;
+        };
+        let range = self.text.range(last.ident.span());
+        for variant in variants {
+            self.push(Operator::ChangeVariant, range.clone(), variant);
+        }
+    }
+}
+
+impl Visit<'_> for Generator<'_> {
+    fn visit_item_mod(&mut self, item: &ItemMod) {
+        if !is_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &ItemFn) {
+        if is_test(&item.attrs) {
+            return;
+        }
+        self.functions.push(Function::new(&item.sig));
+        visit::visit_item_fn(self, item);
+        self.functions.pop();
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &ImplItemFn) {
+        self.functions.push(Function::new(&item.sig));
+        visit::visit_impl_item_fn(self, item);
+        self.functions.pop();
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Binary(binary) => self.flip_comparison(&binary.op),
+            Expr::Path(path) => self.change_variant(path),
+            Expr::Call(call) => {
+                if let Expr::Path(callee) = &*call.func {
+                    let name = callee.path.segments.last().map(|s| s.ident.to_string());
+                    let recursive = callee.path.segments.len() == 1
+                        && self.functions.last().map(|f| &f.name) == name.as_ref();
+                    if recursive {
+                        self.drop_call(call);
+                    } else if let Some(name) = name {
+                        self.swap_children(call, &name);
+                    }
+                }
+            },
+            _ => {},
+        }
+        visit::visit_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "enum Color {
+    R,
+    B,
+}
+
+enum Tree {
+    E,
+    T(Color, Box<Tree>, i32, Box<Tree>),
+}
+
+use Color::*;
+use Tree::*;
+
+fn insert(k: i32, t: Tree) -> Tree {
+    match t {
+        E => T(R, Box::new(E), k, Box::new(E)),
+        T(c, l, x, r) => {
+            /*| insert_1 */
+            if k < x { T(c, Box::new(insert(k, *l)), x, r) } else { T(c, l, x, r) }
+            /*|| insert_2 */
+            /*|
+            T(c, l, x, r)
+            */
+            /* |*/
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn test() {
+        assert!(1 < 2);
+    }
+}
+";
+
+    #[test]
+    fn test_generate() {
+        let mutations = generate(SOURCE).unwrap();
+        let describe = |operator| {
+            mutations
+                .iter()
+                .filter(|m| m.operator == operator)
+                .map(|m| format!("{} {} {}", m.span, m.original, m.replacement))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(describe(Operator::FlipComparison), vec!["19:18 - 19:18 < >="]);
+        assert_eq!(describe(Operator::ChangeVariant), vec!["16:16 - 16:16 R B"]);
+        // Swapping the two `Box::new(E)` of line 16 would change nothing.
+        assert_eq!(
+            describe(Operator::SwapChildren),
+            vec![
+                "19:29 - 19:57 Box::new(insert(k, *l)), x, r r, x, Box::new(insert(k, *l))",
+                "19:74 - 19:80 l, x, r r, x, l",
+            ]
+        );
+        assert_eq!(describe(Operator::DropCall), vec!["19:38 - 19:50 insert(k, *l) *l"]);
+        assert!(mutations.iter().all(|m| m.function.as_deref() == Some("insert")));
+
+        let flip = &mutations[mutations.len() - 1];
+        let mutated = flip.apply(SOURCE);
+        assert_eq!(mutated.len(), SOURCE.len() - flip.original.len() + flip.replacement.len());
+        assert!(generate(&mutated).is_ok());
+        assert!(generate("fn f( {").is_err());
+    }
+}
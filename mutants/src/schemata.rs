@@ -0,0 +1,252 @@
+//! Mutant schemata: rewrites a marauder annotated source so that every mutant
+//! is compiled in and chosen at runtime through [`crate::switch::active`].
+//!
+//! Blocks holding expressions become an `if` chain that falls back to the
+//! base code. Blocks holding match arms get each mutant arm repeated with a
+//! guard in front of the base arms, which keeps the match exhaustive. The
+//! marauder markers and the commented mutants are kept in the output, so the
+//! generated file still parses as ground truth for [`crate::marauder`].
+
+use std::ops::Range;
+
+use crate::marauder::{self, Block, ParseError, Position};
+
+const ACTIVE: &str = "::faultloc_mutants::switch::active";
+
+/// The depth-0 code of `text`: byte offsets of characters outside of
+/// brackets, string literals and comments.
+fn top_level(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut offsets = vec![];
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += text[i..].find("*/").map(|end| end + 1).unwrap_or(text.len() - i);
+            },
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    offsets.push(i);
+                }
+            },
+            _ if depth == 0 => offsets.push(i),
+            _ => {},
+        }
+        i += 1;
+    }
+    offsets
+}
+
+/// Splits a run of match arms into `(pattern, arrow, end)` ranges, where
+/// `arrow` is the offset of the arm's `=>`.
+fn arms(text: &str) -> Vec<(usize, usize, usize)> {
+    let offsets = top_level(text);
+    let bytes = text.as_bytes();
+    let mut arms = vec![];
+    let mut start = 0;
+    let mut arrow = None;
+    for (n, &i) in offsets.iter().enumerate() {
+        match arrow {
+            None if bytes[i] == b'=' && bytes.get(i + 1) == Some(&b'>') => arrow = Some(i),
+            Some(a) if i > a + 1 => {
+                // The body ends at a top-level comma, or at the closing brace
+                // of a block body that is not followed by one.
+                let body = text[a + 2..].trim_start();
+                let block_body = body.starts_with('{');
+                let ends = bytes[i] == b','
+                    || (block_body
+                        && bytes[i] == b'}'
+                        && offsets.get(n + 1).is_none_or(|&next| {
+                            text[i + 1..next].trim().is_empty() && bytes[next] != b','
+                        }));
+                if ends {
+                    arms.push((start, a, i + 1));
+                    start = i + 1;
+                    arrow = None;
+                }
+            },
+            _ => {},
+        }
+    }
+    if let Some(a) = arrow {
+        arms.push((start, a, text.len()));
+    }
+    arms
+}
+
+fn is_arms(text: &str) -> bool {
+    !arms(text).is_empty()
+}
+
+fn guard(text: &str, mutant: &str) -> Result<String, String> {
+    let mut guarded = String::new();
+    let mut last = 0;
+    for (start, arrow, end) in arms(text) {
+        let pattern = &text[start..arrow];
+        if pattern.split_whitespace().any(|word| word == "if") {
+            return Err(format!(
+                "mutant {} has a guarded arm, which schemata cannot extend",
+                mutant
+            ));
+        }
+        guarded.push_str(&text[last..arrow]);
+        guarded.push_str(&format!("if {}(\"{}\") ", ACTIVE, mutant));
+        guarded.push_str(&text[arrow..end]);
+        last = end;
+    }
+    guarded.push_str(&text[last..]);
+    Ok(guarded)
+}
+
+fn dispatch(block: &Block) -> Result<String, String> {
+    let base = block.base();
+    let mutants = block.mutants().collect::<Vec<_>>();
+
+    if is_arms(&base.text) {
+        let mut code = String::new();
+        for mutant in &mutants {
+            let name = mutant.name.as_deref().unwrap_or_default();
+            if !is_arms(&mutant.text) {
+                return Err(format!("mutant {} is not a match arm like its base", name));
+            }
+            code.push_str(&guard(&mutant.text, name)?);
+            code.push('\n');
+        }
+        code.push_str(&base.text);
+        return Ok(code);
+    }
+
+    let mut code = String::new();
+    for (i, mutant) in mutants.iter().enumerate() {
+        let name = mutant.name.as_deref().unwrap_or_default();
+        if i > 0 {
+            code.push_str(" else ");
+        }
+        code.push_str(&format!("if {}(\"{}\") {{\n{}\n}}", ACTIVE, name, mutant.text));
+    }
+    if mutants.is_empty() {
+        code.push_str(&base.text);
+    } else {
+        code.push_str(&format!(" else {{\n{}\n}}", base.text));
+    }
+    Ok(code)
+}
+
+/// Rewrites `source` into its mutant schemata, and appends a `MUTANTS`
+/// constant listing every mutant that can be switched on.
+pub fn rewrite(source: &str) -> Result<String, ParseError> {
+    let blocks = marauder::parse(source)?;
+    let error = |block: &Block, message: String| ParseError {
+        position: Position { line: block.span.start.line, col: block.span.start.col },
+        message,
+    };
+
+    let mut replacements: Vec<(Range<usize>, String)> = vec![];
+    let mut names = vec![];
+    for block in &blocks {
+        if !block.base().active {
+            return Err(error(block, "schemata needs the base code to be live".to_string()));
+        }
+        replacements
+            .push((block.base().range.clone(), dispatch(block).map_err(|e| error(block, e))?));
+        names.extend(block.mutants().filter_map(|mutant| mutant.name.clone()));
+    }
+
+    let mut schemata = String::with_capacity(source.len() * 2);
+    let mut last = 0;
+    for (range, code) in replacements {
+        schemata.push_str(&source[last..range.start]);
+        schemata.push_str(&code);
+        last = range.end;
+    }
+    schemata.push_str(&source[last..]);
+
+    schemata.push_str("\n/// Every mutant compiled into this schemata.\n");
+    schemata.push_str(&format!("pub const MUTANTS: &[&str] = &{:?};\n", names));
+    Ok(schemata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arms() {
+        let text = "(B, a) => T(a),\n(R, b) => {\n    b\n}\n(_, c) => c";
+        let arms = arms(text).iter().map(|&(s, _, e)| text[s..e].trim()).collect::<Vec<_>>();
+        assert_eq!(arms, vec!["(B, a) => T(a),", "(R, b) => {\n    b\n}", "(_, c) => c"]);
+        assert!(!is_arms("if k < k2 { match k { 0 => 1, _ => 2 } } else { 3 }"));
+        assert!(!is_arms("let tp = del(x, t, FUEL)?;\nSome(blacken(tp))"));
+    }
+
+    #[test]
+    fn test_rewrite_expression_block() {
+        let source = "fn f(k: i32) -> i32 {
+    /*| f */
+    k + 1
+    /*|| f_1 */
+    /*|
+    k
+    */
+    /*|| f_2 */
+    /*|
+    k + 2
+    */
+    /* |*/
+}
+";
+        let schemata = rewrite(source).unwrap();
+        assert!(schemata.contains(
+            "if ::faultloc_mutants::switch::active(\"f_1\") {\nk\n} else if ::faultloc_mutants::switch::active(\"f_2\") {\nk + 2\n} else {\nk + 1\n}"
+        ));
+        assert!(schemata.ends_with("pub const MUTANTS: &[&str] = &[\"f_1\", \"f_2\"];\n"));
+
+        // The markers survive, so ground truth can still be read from it.
+        let blocks = marauder::parse(&schemata).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert!(blocks[0].mutant("f_2").is_some());
+    }
+
+    #[test]
+    fn test_rewrite_arm_block() {
+        let source = "fn f(c: Color) -> Color {
+    match c {
+        (E, r) => r,
+        /*| */
+        (T(l), r) => {
+            l
+        }
+        /*|| f_1 */
+        /*|
+        (T(l), T(r)) => r,
+        */
+        /* |*/
+    }
+}
+";
+        let schemata = rewrite(source).unwrap();
+        assert!(schemata.contains(
+            "(T(l), T(r)) if ::faultloc_mutants::switch::active(\"f_1\") => r,\n(T(l), r) => {\n            l\n        }"
+        ));
+    }
+
+    #[test]
+    fn test_rewrite_rejects_mismatched_mutant() {
+        let source = "/*| */\n(B, a) => a,\n/*|| f_1 */\n/*|\na\n*/\n/* |*/\n";
+        assert!(rewrite(source).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Runtime selection of the mutants compiled in by [`crate::schemata`].
+//!
+//! The selection is fixed on first use: either explicitly through [`select`],
+//! or from the comma separated names in the [`ENV`] environment variable.
+//! With nothing selected, every block runs its base code.
+
+use std::sync::OnceLock;
+
+/// Environment variable holding the mutants to switch on, e.g.
+/// `FAULTLOC_MUTANTS=insert_1,delete_4`.
+pub const ENV: &str = "FAULTLOC_MUTANTS";
+
+static SELECTED: OnceLock<Vec<String>> = OnceLock::new();
+
+fn parse(names: &str) -> Vec<String> {
+    names.split(',').map(str::trim).filter(|name| !name.is_empty()).map(String::from).collect()
+}
+
+/// Switches on `names`, overriding [`ENV`]. Fails once the selection has been
+/// read or made, as code may already have run with it.
+pub fn select(names: &[String]) -> Result<(), String> {
+    SELECTED.set(names.to_vec()).map_err(|_| "Mutants were already selected".to_string())
+}
+
+pub fn selected() -> &'static [String] {
+    SELECTED.get_or_init(|| std::env::var(ENV).map(|names| parse(&names)).unwrap_or_default())
+}
+
+/// Whether the mutant `name` is switched on. Called by the generated code at
+/// every marauder block.
+pub fn active(name: &str) -> bool {
+    selected().iter().any(|selected| selected == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("insert_1, delete_4,"), vec!["insert_1", "delete_4"]);
+        assert!(parse("").is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Task {
+    pub strategy: String,
+    pub property: String,
+}
+
+/// One entry of a `tests/*.json` matrix: every task is run `trials` times
+/// against the workload with `mutations` switched on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Experiment {
+    pub language: String,
+    pub workload: String,
+    pub mutations: Vec<String>,
+    pub trials: usize,
+    /// Per trial, in seconds.
+    pub timeout: u64,
+    pub tasks: Vec<Task>,
+}
+
+impl Experiment {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+}
+
+pub fn load(path: &Path) -> Result<Vec<Experiment>, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("invalid experiments in {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_matrix() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/bst.json");
+        let experiments = load(&path).unwrap();
+        assert!(!experiments.is_empty());
+        let first = &experiments[0];
+        assert_eq!((first.language.as_str(), first.workload.as_str()), ("Rust", "BST"));
+        assert_eq!(first.mutations, vec!["insert_1"]);
+        assert_eq!(first.timeout(), Duration::from_secs(180));
+        assert!(first.tasks.contains(&Task {
+            strategy: "crabcheck".to_string(),
+            property: "InsertPost".to_string()
+        }));
+    }
+
+    #[test]
+    fn test_multi_fault_matrix() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        for experiment in load(&root.join("tests/multi.json")).unwrap() {
+            assert!(experiment.mutations.len() > 1);
+            let source = root
+                .join("workloads")
+                .join(&experiment.language)
+                .join(&experiment.workload)
+                .join("src/implementation.rs");
+            let source = std::fs::read_to_string(source).unwrap();
+            let names = experiment.mutations.iter().map(String::as_str).collect::<Vec<_>>();
+            // Mutants of the same block cannot be live together.
+            assert!(
+                faultloc_mutants::marauder::apply(&source, &names).is_ok(),
+                "{} {:?}",
+                experiment.workload,
+                names
+            );
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod experiment;
+pub mod mbfl;
+pub mod repair;
+pub mod workload;
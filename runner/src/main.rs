@@ -0,0 +1,303 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, RandomState},
+    path::Path,
+    process::ExitCode,
+    time::SystemTime,
+};
+
+use faultloc_analysis::{
+    coverage::{Outcome, Spectrum},
+    formula::Formula,
+};
+use faultloc_runner::{
+    experiment, mbfl, repair,
+    workload::{self, Mode, RUSTFLAGS, Workload},
+};
+use faultloc_store::{Key, Reader, RegionRecord, Status, Tests, Trial, Writer, timestamp};
+
+fn usage(program: &str) {
+    eprintln!(
+        "Usage: {} <tests/*.json>... [--workloads <dir>] [--store <store.jsonl>] [--schemata] [--seed <n>] [--dry-run]",
+        program
+    );
+    eprintln!(
+        "       {} <tests/*.json>... --mbfl <mbfl.jsonl> [--workloads <dir>] [--seed <n>] [--tests <n>] [--limit <n>] [--dry-run]",
+        program
+    );
+    eprintln!(
+        "       {} <tests/*.json>... --repair <repairs.jsonl> [--store <store.jsonl>] [--workloads <dir>] [--top <k>] [--formula <name>] [--seed <n>] [--tests <n>] [--dry-run]",
+        program
+    );
+    eprintln!("  --workloads  Directory holding <language>/<workload> (default: workloads)");
+    eprintln!(
+        "  --store      Where trials are appended; stored trials are skipped (default: store.jsonl)"
+    );
+    eprintln!("  --schemata   Build each workload once and select mutants at runtime");
+    eprintln!(
+        "  --seed       Seed trial t of every task with n + t (default: a random n, printed to replay the run)"
+    );
+    eprintln!(
+        "               Seeded trials are stored under <strategy>-seeded, as the harness generates their inputs"
+    );
+    eprintln!("  --dry-run    List the pending trials without running them");
+    eprintln!(
+        "  --mbfl       Run every input of each task against mutants of the seeded faults instead, for faultloc-analysis mbfl"
+    );
+    eprintln!(
+        "  --repair     Try patches at the most suspicious regions of the stored trials instead, keeping those every property passes on"
+    );
+    eprintln!(
+        "  --tests      How many inputs each MBFL or repair run evaluates (default: {})",
+        mbfl::TESTS
+    );
+    eprintln!("  --limit      How many mutants to run at most, spread over the source");
+    eprintln!("  --top        How many of the most suspicious regions to patch (default: 5)");
+    eprintln!("  --formula    The formula regions are ranked with for --repair (default: ochiai)");
+}
+
+fn value<'a>(args: &mut impl Iterator<Item = &'a String>, flag: &str) -> Result<&'a str, String> {
+    args.next().map(String::as_str).ok_or_else(|| format!("Missing value for {}", flag))
+}
+
+/// The trials already in the store. Lines that are not records, such as one
+/// cut short by an interrupted run, are reported and their trials redone.
+fn completed(path: &Path) -> Result<HashSet<Key>, String> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    let mut keys = HashSet::new();
+    for record in Reader::open(path).map_err(|e| e.to_string())? {
+        match record {
+            Ok(record) => {
+                keys.insert(record.data.key);
+            },
+            Err(e) => eprintln!("Ignoring {}: {}", path.display(), e),
+        }
+    }
+    Ok(keys)
+}
+
+fn regions(spectrum: &Spectrum) -> Vec<RegionRecord> {
+    spectrum
+        .regions
+        .iter()
+        .map(|(region, counts)| RegionRecord {
+            file: region.file.clone(),
+            start_line: region.start_line,
+            start_col: region.start_col,
+            end_line: region.end_line,
+            end_col: region.end_col,
+            function: spectrum.functions.get(region).cloned(),
+            ef: counts.ef,
+            ep: counts.ep,
+        })
+        .collect()
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut files = vec![];
+    let mut workloads = "workloads";
+    let mut store = "store.jsonl";
+    let mut mode = Mode::Source;
+    let mut seed = None;
+    let mut dry_run = false;
+    let mut results = None;
+    let mut tests = None;
+    let mut limit = None;
+    let mut repairs = None;
+    let mut top = None;
+    let mut formula = None;
+
+    let number = |value: &str, flag: &str| {
+        value.parse::<usize>().map_err(|_| format!("Invalid value for {}: {}", flag, value))
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workloads" => workloads = value(&mut args, arg)?,
+            "--store" => store = value(&mut args, arg)?,
+            "--schemata" => mode = Mode::Schemata,
+            "--seed" => {
+                let value = value(&mut args, arg)?;
+                seed = Some(value.parse::<u64>().map_err(|_| format!("Invalid seed: {}", value))?);
+            },
+            "--dry-run" => dry_run = true,
+            "--mbfl" => results = Some(value(&mut args, arg)?),
+            "--tests" => tests = Some(number(value(&mut args, arg)?, arg)?),
+            "--limit" => limit = Some(number(value(&mut args, arg)?, arg)?),
+            "--repair" => repairs = Some(value(&mut args, arg)?),
+            "--top" => top = Some(number(value(&mut args, arg)?, arg)?),
+            "--formula" => formula = Some(value(&mut args, arg)?.parse::<Formula>()?),
+            _ if !arg.starts_with("--") => files.push(arg.as_str()),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    if files.is_empty() {
+        return Err("Missing experiment file".to_string());
+    }
+    if results.is_some() && repairs.is_some() {
+        return Err("--mbfl and --repair cannot be combined".to_string());
+    }
+    if repairs.is_none() && (top.is_some() || formula.is_some()) {
+        return Err("--top and --formula only apply to --repair".to_string());
+    }
+    if let Some(repairs) = repairs {
+        if limit.is_some() {
+            return Err("--limit only applies to --mbfl".to_string());
+        }
+        let options = repair::Options {
+            seed: seed.unwrap_or(0),
+            tests: tests.unwrap_or(mbfl::TESTS),
+            top: top.unwrap_or(5),
+            formula: formula.unwrap_or(Formula::Ochiai),
+        };
+        for file in files {
+            for experiment in experiment::load(Path::new(file))? {
+                let workload =
+                    Workload::new(Path::new(workloads), &experiment.language, &experiment.workload);
+                repair::run(
+                    &workload,
+                    &experiment,
+                    &options,
+                    Path::new(store),
+                    Path::new(repairs),
+                    dry_run,
+                )?;
+            }
+        }
+        return Ok(());
+    }
+    if let Some(results) = results {
+        let options =
+            mbfl::Options { seed: seed.unwrap_or(0), tests: tests.unwrap_or(mbfl::TESTS), limit };
+        for file in files {
+            for experiment in experiment::load(Path::new(file))? {
+                let workload =
+                    Workload::new(Path::new(workloads), &experiment.language, &experiment.workload);
+                mbfl::run(&workload, &experiment, &options, Path::new(results), dry_run)?;
+            }
+        }
+        return Ok(());
+    }
+    if tests.is_some() || limit.is_some() {
+        return Err("--tests only applies to --mbfl and --repair, --limit to --mbfl".to_string());
+    }
+
+    // Every trial is seeded, so that any of them can be replayed.
+    let seed = seed.unwrap_or_else(|| RandomState::new().hash_one(SystemTime::now()));
+    println!("Seed: {}", seed);
+
+    let store = Path::new(store);
+    let completed = completed(store)?;
+    let mut writer = None;
+    // What each workload's binary was last built with.
+    let mut built = HashMap::new();
+
+    for file in files {
+        for experiment in experiment::load(Path::new(file))? {
+            let workload =
+                Workload::new(Path::new(workloads), &experiment.language, &experiment.workload);
+            let key = |strategy: &str, property: &str, trial| Key {
+                workload: experiment.workload.clone(),
+                mutations: experiment.mutations.clone(),
+                strategy: workload::seeded(strategy),
+                property: property.to_string(),
+                trial,
+            };
+            let pending = experiment
+                .tasks
+                .iter()
+                .flat_map(|task| (0..experiment.trials).map(move |trial| (task, trial)))
+                .filter(|(task, trial)| {
+                    !completed.contains(&key(&task.strategy, &task.property, *trial))
+                })
+                .collect::<Vec<_>>();
+
+            println!(
+                "{} {}: {} pending trials",
+                experiment.workload,
+                experiment.mutations.join(","),
+                pending.len()
+            );
+            if pending.is_empty() || dry_run {
+                for (task, trial) in &pending {
+                    println!(" {} {} trial {}", task.strategy, task.property, trial);
+                }
+                continue;
+            }
+
+            // A schemata build serves every mutation set of its workload.
+            let build = match mode {
+                Mode::Schemata => None,
+                Mode::Source => Some(experiment.mutations.clone()),
+            };
+            if built.get(&workload.path) != Some(&build) {
+                println!("Building {} ({:?})", workload.path.display(), mode);
+                workload.build(&experiment.mutations, mode)?;
+                built.insert(workload.path.clone(), build);
+            }
+
+            let rustc = workload.rustc()?;
+            let writer = match &mut writer {
+                Some(writer) => writer,
+                None => writer.insert(Writer::open(store).map_err(|e| e.to_string())?),
+            };
+            for (task, trial) in pending {
+                let seed = seed.wrapping_add(trial as u64);
+                let run =
+                    workload.run(task, &experiment.mutations, mode, seed, experiment.timeout())?;
+                let executions = workload.export()?;
+                let spectrum = Spectrum::from_executions(&executions);
+                let discarded = executions.iter().filter(|e| e.outcome == Outcome::Discard).count();
+
+                println!(
+                    " {} {} trial {}: {:?} in {:.2}s, {} passed, {} failed, {} discarded",
+                    task.strategy,
+                    task.property,
+                    trial,
+                    run.status,
+                    run.time.as_secs_f64(),
+                    spectrum.passed,
+                    spectrum.failed,
+                    discarded
+                );
+                if let Status::Crashed(_) = run.status {
+                    eprintln!("{}", run.output);
+                }
+
+                writer
+                    .append(Trial {
+                        key: key(&task.strategy, &task.property, trial),
+                        status: run.status,
+                        seed: Some(seed),
+                        tests: Tests {
+                            passed: spectrum.passed,
+                            failed: spectrum.failed,
+                            discarded,
+                        },
+                        rustc: rustc.clone(),
+                        rustflags: RUSTFLAGS.to_string(),
+                        time: run.time.as_secs_f64(),
+                        date: Some(timestamp(SystemTime::now())),
+                        output: run.output,
+                        regions: regions(&spectrum),
+                    })
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    match run(&args[1..]) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            usage(&args[0]);
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -0,0 +1,273 @@
+//! Mutation-based fault localization: generates mutants on top of an
+//! experiment's seeded faults with [`faultloc_mutants::operators`], and
+//! records which inputs of each property pass and fail against each of them
+//! for `faultloc-analysis mbfl`.
+//!
+//! Every input of a seeded run is evaluated, so unlike a trial a run does not
+//! stop at the first failure. Mutants are built without coverage
+//! instrumentation, into [`Workload::plain_target`]; those that do not
+//! compile are stillborn and skipped.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::Path,
+    time::Duration,
+};
+
+use faultloc_analysis::mbfl::{self, Kills, MutantRun};
+use faultloc_mutants::{
+    marauder,
+    operators::{self, Mutation},
+};
+use faultloc_store::Status;
+use serde::Serialize;
+
+use crate::{
+    experiment::{Experiment, Task},
+    workload::{Run, Workload},
+};
+
+/// How much longer than the seeded faults alone a mutant or a patch may run
+/// before it counts as looping.
+pub(crate) const SLOWDOWN: u32 = 10;
+
+/// How many inputs each property is run on when not given. The harness
+/// defaults of some workloads only stop at the experiment timeout, and a run
+/// against the seeded faults alone has to finish.
+pub const TESTS: usize = 1000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub seed: u64,
+    /// How many inputs each property is run on.
+    pub tests: usize,
+    /// How many mutants to run at most, spread evenly over the source.
+    pub limit: Option<usize>,
+}
+
+/// The outcomes `faultloc --outcomes` printed. A run that crashed or timed
+/// out failed the input it was on, and never got to the rest.
+pub(crate) fn outcomes(run: &Run, tests: usize) -> String {
+    let mut outcomes = run
+        .output
+        .lines()
+        .find_map(|line| line.strip_prefix("Outcomes: "))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if run.status != Status::Finished {
+        if outcomes.len() < tests {
+            outcomes.push('F');
+        }
+        while outcomes.len() < tests {
+            outcomes.push('?');
+        }
+    }
+    outcomes
+}
+
+/// Checks that `task` ran to the end against the seeded faults alone: the
+/// inputs a mutant or patch is compared on are those the run evaluated.
+pub(crate) fn finished(task: &Task, status: &Status) -> Result<(), String> {
+    match status {
+        Status::Finished => Ok(()),
+        status => Err(format!(
+            "{} {} did not finish with the seeded faults alone ({:?}), lower --tests",
+            task.strategy, task.property, status
+        )),
+    }
+}
+
+/// Every `limit`th mutation, give or take, so that a limited run still
+/// touches the whole file.
+fn sample<T: Clone>(items: Vec<T>, limit: Option<usize>) -> Vec<T> {
+    match limit {
+        Some(limit) if limit < items.len() => {
+            let step = items.len() as f64 / limit as f64;
+            (0..limit).map(|i| items[(i as f64 * step) as usize].clone()).collect()
+        },
+        _ => items,
+    }
+}
+
+/// Appends `record` to a JSON-lines results file.
+pub(crate) fn append(path: &Path, record: &impl Serialize) -> Result<(), String> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+    let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Runs every task of `experiment` against its seeded faults alone, then
+/// against each mutant generated on top of them, appending to `results`.
+/// Runs already in `results` are skipped, and so are mutants with nothing
+/// left to run.
+pub fn run(
+    workload: &Workload,
+    experiment: &Experiment,
+    options: &Options,
+    results: &Path,
+    dry_run: bool,
+) -> Result<(), String> {
+    let names = experiment.mutations.iter().map(String::as_str).collect::<Vec<_>>();
+    let seeded = marauder::apply(&workload.source()?, &names)
+        .map_err(|e| format!("{}: {}", workload.path.display(), e))?;
+    let mutations = sample(
+        operators::generate(&seeded)
+            .map_err(|e| format!("{}/src/implementation.rs:{}", workload.path.display(), e))?,
+        options.limit,
+    );
+
+    let previous = if results.exists() { mbfl::load(results)? } else { vec![] };
+    let same = |run: &MutantRun| {
+        run.workload == experiment.workload
+            && run.mutations == experiment.mutations
+            && run.seed == options.seed
+    };
+    // A run of the seeded faults alone that did not finish is run again.
+    let done = previous
+        .iter()
+        .filter(|run| same(run) && (run.mutant.is_some() || run.status == Status::Finished))
+        .map(|run| (run.strategy.clone(), run.property.clone(), run.mutant.clone()))
+        .collect::<HashSet<_>>();
+    let pending = |mutant: &Option<Mutation>| {
+        experiment
+            .tasks
+            .iter()
+            .filter(|task| {
+                !done.contains(&(task.strategy.clone(), task.property.clone(), mutant.clone()))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    println!(
+        "{} {}: {} mutants on top of the seeded faults",
+        experiment.workload,
+        experiment.mutations.join(","),
+        mutations.len()
+    );
+    if dry_run {
+        for mutation in &mutations {
+            println!(" {}", mutation);
+        }
+        return Ok(());
+    }
+
+    let target = workload.plain_target()?;
+    let record = |task: &Task, mutant: Option<Mutation>, run: &Run, tests| MutantRun {
+        workload: experiment.workload.clone(),
+        mutations: experiment.mutations.clone(),
+        strategy: task.strategy.clone(),
+        property: task.property.clone(),
+        seed: options.seed,
+        mutant,
+        status: run.status.clone(),
+        time: run.time.as_secs_f64(),
+        outcomes: outcomes(run, tests),
+    };
+
+    // The seeded faults alone set the number of inputs and the time a mutant
+    // may take.
+    let mut baseline = previous
+        .into_iter()
+        .filter(|run| same(run) && run.mutant.is_none() && run.status == Status::Finished)
+        .map(|run| ((run.strategy, run.property), (run.outcomes, run.time)))
+        .collect::<HashMap<_, _>>();
+    let tasks = pending(&None);
+    if !tasks.is_empty() {
+        println!("Building {} with the seeded faults alone", workload.path.display());
+        workload.build_plain(&seeded, &target)?;
+        for task in tasks {
+            let run = workload.outcomes(
+                &target,
+                task,
+                options.seed,
+                Some(options.tests),
+                experiment.timeout(),
+            )?;
+            finished(task, &run.status)?;
+            let run = record(task, None, &run, options.tests);
+            println!(
+                " {} {}: {:?}, {} failing of {} inputs",
+                task.strategy,
+                task.property,
+                run.status,
+                run.outcomes.matches('F').count(),
+                run.outcomes.len()
+            );
+            append(results, &run)?;
+            baseline.insert((run.strategy, run.property), (run.outcomes, run.time));
+        }
+    }
+
+    let mut stillborn = 0;
+    for (i, mutation) in mutations.iter().enumerate() {
+        let mutant = Some(mutation.clone());
+        let tasks = pending(&mutant);
+        if tasks.is_empty() {
+            continue;
+        }
+        if let Err(e) = workload.build_plain(&mutation.apply(&seeded), &target) {
+            // Only a compile error makes a mutant stillborn.
+            if !e.starts_with("cargo build failed") {
+                return Err(e);
+            }
+            println!(" {}/{} {}: stillborn", i + 1, mutations.len(), mutation);
+            stillborn += 1;
+            continue;
+        }
+
+        let mut kills = Kills::default();
+        for task in tasks {
+            let key = (task.strategy.clone(), task.property.clone());
+            let Some((before, time)) = baseline.get(&key) else {
+                return Err(format!("No run of {} {} with the seeded faults alone", key.0, key.1));
+            };
+            let timeout = experiment
+                .timeout()
+                .min(Duration::from_secs_f64(*time) * SLOWDOWN + Duration::from_secs(1));
+            let tests = before.len();
+            let run = workload.outcomes(&target, task, options.seed, Some(tests), timeout)?;
+            let run = record(task, mutant.clone(), &run, tests);
+            kills.add(Kills::new(before, &run.outcomes));
+            append(results, &run)?;
+        }
+        println!(
+            " {}/{} {}: killed {} failing and {} passing inputs",
+            i + 1,
+            mutations.len(),
+            mutation,
+            kills.failing,
+            kills.passing
+        );
+    }
+    if stillborn > 0 {
+        println!("{} of {} mutants did not compile", stillborn, mutations.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcomes() {
+        let run = |status, output: &str| Run {
+            status,
+            output: output.to_string(),
+            time: Duration::from_secs(1),
+        };
+        let finished = run(Status::Finished, "Outcomes: PPFD\n");
+        assert_eq!(outcomes(&finished, 4), "PPFD");
+        assert_eq!(outcomes(&run(Status::TimedOut, "Outcomes: PP"), 5), "PPF??");
+        assert_eq!(outcomes(&run(Status::Crashed(Some(134)), "Building\n"), 2), "F?");
+
+        let sampled = sample((0..10).map(|i| i.to_string()).collect::<Vec<_>>(), Some(3));
+        assert_eq!(sampled, vec!["0", "3", "6"]);
+    }
+}
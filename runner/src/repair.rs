@@ -0,0 +1,405 @@
+//! An automatic repair trial after localization: patches the most suspicious
+//! regions of an experiment's stored trials, and keeps the patches that make
+//! every property of the experiment pass.
+//!
+//! Patches come from the mutation operators of [`faultloc_mutants::operators`]
+//! and from the other variants of the marauder blocks the regions touch, the
+//! base code included. A plausible patch passes every input the properties
+//! are run on; that is all it promises, as a patch can overfit the inputs.
+//! How far down the ranking the first plausible patch is found measures the
+//! localization from the other end.
+
+use std::{fmt::Display, path::Path, time::Duration};
+
+use faultloc_analysis::{
+    aggregate::{self, Weighting},
+    coverage::{Region, Spectrum},
+    formula::Formula,
+};
+use faultloc_mutants::{
+    marauder::{self, Position, Span},
+    operators::{self, Mutation},
+};
+use faultloc_store::{Reader, Status, Trial};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    experiment::{Experiment, Task},
+    mbfl::{SLOWDOWN, append, finished, outcomes},
+    workload::Workload,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub seed: u64,
+    /// How many inputs each property is run on.
+    pub tests: usize,
+    /// How many of the most suspicious regions to patch.
+    pub top: usize,
+    pub formula: Formula,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Patch {
+    /// A generated mutation of the live code.
+    Mutation(Mutation),
+    /// Another variant of a marauder block: its base code when `variant` is
+    /// `None`.
+    Sibling { block: String, span: Span, variant: Option<String> },
+}
+
+impl Patch {
+    pub fn span(&self) -> Span {
+        match self {
+            Patch::Mutation(mutation) => mutation.span,
+            Patch::Sibling { span, .. } => *span,
+        }
+    }
+
+    /// `seeded`, the source the patch was found in, with the patch applied.
+    fn apply(&self, seeded: &str) -> Result<String, String> {
+        match self {
+            Patch::Mutation(mutation) => Ok(mutation.apply(seeded)),
+            Patch::Sibling { span, variant, .. } => {
+                let blocks = marauder::parse(seeded).map_err(|e| e.to_string())?;
+                let block = blocks
+                    .iter()
+                    .find(|block| block.span == *span)
+                    .ok_or_else(|| format!("no block at {}", span))?;
+                let variant = block
+                    .variants
+                    .iter()
+                    .find(|v| v.name == *variant)
+                    .ok_or_else(|| format!("no such variant in the block at {}", span))?;
+                Ok(marauder::switch(seeded, block, variant))
+            },
+        }
+    }
+}
+
+impl Display for Patch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Patch::Mutation(mutation) => write!(f, "{}", mutation),
+            Patch::Sibling { block, span, variant } => {
+                let block = if block.is_empty() { "block" } else { block };
+                match variant {
+                    Some(variant) => write!(f, "{} at {} switched to {}", block, span, variant),
+                    None => write!(f, "{} at {} switched to its base code", block, span),
+                }
+            },
+        }
+    }
+}
+
+/// How one property did on a patched build.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub strategy: String,
+    pub property: String,
+    pub status: Status,
+    pub passed: usize,
+    pub failed: usize,
+    pub discarded: usize,
+    /// Inputs a crashed or killed run never got to.
+    pub unknown: usize,
+}
+
+impl Check {
+    fn new(task: &Task, status: Status, outcomes: &str) -> Check {
+        Check {
+            strategy: task.strategy.clone(),
+            property: task.property.clone(),
+            status,
+            passed: outcomes.matches('P').count(),
+            failed: outcomes.matches('F').count(),
+            discarded: outcomes.matches('D').count(),
+            unknown: outcomes.matches('?').count(),
+        }
+    }
+
+    fn passes(&self) -> bool {
+        self.status == Status::Finished && self.failed == 0 && self.unknown == 0
+    }
+}
+
+/// One patch tried, as recorded in the results file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub workload: String,
+    pub mutations: Vec<String>,
+    pub seed: u64,
+    /// The rank of the most suspicious region the patch touches.
+    pub rank: usize,
+    pub patch: Patch,
+    pub compiled: bool,
+    /// The properties run, up to the first one that failed.
+    pub checks: Vec<Check>,
+    pub plausible: bool,
+}
+
+/// The `top` most suspicious regions of `src/implementation.rs`, ranked over
+/// every stored trial of the experiment like `faultloc-analysis aggregate`.
+fn suspicious(trials: &[Trial], formula: Formula, top: usize) -> Vec<Region> {
+    let mut spectra = Vec::<(String, Spectrum)>::new();
+    for trial in trials {
+        let spectrum = Spectrum::from_trial(trial);
+        match spectra.iter_mut().find(|(property, _)| *property == trial.key.property) {
+            Some((_, merged)) => merged.merge(&spectrum),
+            None => spectra.push((trial.key.property.clone(), spectrum)),
+        }
+    }
+    aggregate::rank(&spectra, formula, Weighting::Equal)
+        .into_iter()
+        .map(|a| a.ranked.region)
+        .filter(|region| Path::new(&region.file).ends_with("src/implementation.rs"))
+        .take(top)
+        .collect()
+}
+
+fn span(region: &Region) -> Span {
+    Span {
+        start: Position { line: region.start_line, col: region.start_col },
+        end: Position { line: region.end_line, col: region.end_col },
+    }
+}
+
+/// Every patch touching one of `regions`, with the rank of the first region
+/// it touches, most suspicious first. Ties keep source order.
+fn candidates(seeded: &str, regions: &[Region]) -> Result<Vec<(usize, Patch)>, String> {
+    let rank = |patch: Span| regions.iter().position(|r| span(r).intersects(&patch)).map(|i| i + 1);
+    let mut candidates = vec![];
+    for mutation in operators::generate(seeded)? {
+        if let Some(rank) = rank(mutation.span) {
+            candidates.push((rank, Patch::Mutation(mutation)));
+        }
+    }
+    for block in marauder::parse(seeded).map_err(|e| e.to_string())? {
+        let Some(rank) = rank(block.span) else { continue };
+        for variant in block.variants.iter().filter(|variant| !variant.active) {
+            let patch = Patch::Sibling {
+                block: block.name.clone(),
+                span: block.span,
+                variant: variant.name.clone(),
+            };
+            candidates.push((rank, patch));
+        }
+    }
+    candidates.sort_by(|(a, p), (b, q)| a.cmp(b).then(p.span().start.cmp(&q.span().start)));
+    Ok(candidates)
+}
+
+/// The stored trials of the experiment's workload with exactly its mutations
+/// switched on.
+fn trials(store: &Path, experiment: &Experiment) -> Result<Vec<Trial>, String> {
+    let mut mutants = experiment.mutations.clone();
+    mutants.sort();
+    let mut trials = vec![];
+    for record in Reader::open(store).map_err(|e| e.to_string())? {
+        let trial = record.map_err(|e| format!("{}: {}", store.display(), e))?.data;
+        let mut mutations = trial.key.mutations.clone();
+        mutations.sort();
+        if trial.key.workload == experiment.workload && mutations == mutants {
+            trials.push(trial);
+        }
+    }
+    Ok(trials)
+}
+
+/// Tries every patch of the most suspicious regions of `experiment`,
+/// appending each attempt to `results` and reporting the plausible ones.
+pub fn run(
+    workload: &Workload,
+    experiment: &Experiment,
+    options: &Options,
+    store: &Path,
+    results: &Path,
+    dry_run: bool,
+) -> Result<(), String> {
+    let trials = trials(store, experiment)?;
+    if trials.is_empty() {
+        return Err(format!(
+            "No trials of {} with {} in {}, run the experiment first",
+            experiment.workload,
+            experiment.mutations.join(","),
+            store.display()
+        ));
+    }
+    let names = experiment.mutations.iter().map(String::as_str).collect::<Vec<_>>();
+    let seeded = marauder::apply(&workload.source()?, &names)
+        .map_err(|e| format!("{}: {}", workload.path.display(), e))?;
+    let regions = suspicious(&trials, options.formula, options.top);
+    let candidates = candidates(&seeded, &regions)
+        .map_err(|e| format!("{}/src/implementation.rs:{}", workload.path.display(), e))?;
+
+    println!(
+        "{} {}: {} patches of the top {} regions",
+        experiment.workload,
+        experiment.mutations.join(","),
+        candidates.len(),
+        regions.len()
+    );
+    if dry_run {
+        for (rank, patch) in &candidates {
+            println!(" {:>3} {}", rank, patch);
+        }
+        return Ok(());
+    }
+
+    // The seeded faults alone set the number of inputs and the time a patch
+    // may take, and there must be something to repair.
+    let target = workload.plain_target()?;
+    println!("Building {} with the seeded faults alone", workload.path.display());
+    workload.build_plain(&seeded, &target)?;
+    let mut baseline = vec![];
+    let mut failing = 0;
+    for task in &experiment.tasks {
+        let run = workload.outcomes(
+            &target,
+            task,
+            options.seed,
+            Some(options.tests),
+            experiment.timeout(),
+        )?;
+        finished(task, &run.status)?;
+        let check = Check::new(task, run.status.clone(), &outcomes(&run, options.tests));
+        failing += !check.passes() as usize;
+        let tests = check.passed + check.failed + check.discarded + check.unknown;
+        baseline.push((task, tests, run.time));
+    }
+    if failing == 0 {
+        println!("Every property passes with the seeded faults, nothing to repair");
+        return Ok(());
+    }
+
+    let mut plausible = vec![];
+    let mut compiled = 0;
+    for (i, (rank, patch)) in candidates.iter().enumerate() {
+        let mut attempt = Attempt {
+            workload: experiment.workload.clone(),
+            mutations: experiment.mutations.clone(),
+            seed: options.seed,
+            rank: *rank,
+            patch: patch.clone(),
+            compiled: false,
+            checks: vec![],
+            plausible: false,
+        };
+        match workload.build_plain(&patch.apply(&seeded)?, &target) {
+            Ok(()) => attempt.compiled = true,
+            Err(e) if e.starts_with("cargo build failed") => {},
+            Err(e) => return Err(e),
+        }
+        if attempt.compiled {
+            compiled += 1;
+            for (task, tests, time) in &baseline {
+                let timeout = experiment.timeout().min(*time * SLOWDOWN + Duration::from_secs(1));
+                let run = workload.outcomes(&target, task, options.seed, Some(*tests), timeout)?;
+                let check = Check::new(task, run.status.clone(), &outcomes(&run, *tests));
+                let passes = check.passes();
+                attempt.checks.push(check);
+                if !passes {
+                    break;
+                }
+            }
+            attempt.plausible =
+                attempt.checks.iter().all(Check::passes) && attempt.checks.len() == baseline.len();
+        }
+
+        let verdict = match attempt.checks.last() {
+            _ if !attempt.compiled => "does not compile".to_string(),
+            _ if attempt.plausible => "plausible".to_string(),
+            Some(check) => format!(
+                "fails {} on {} of {} inputs",
+                check.property,
+                check.failed,
+                check.passed + check.failed + check.discarded + check.unknown
+            ),
+            None => "not run".to_string(),
+        };
+        println!(" {}/{} rank {} {}: {}", i + 1, candidates.len(), rank, patch, verdict);
+        append(results, &attempt)?;
+        if attempt.plausible {
+            plausible.push((i + 1, attempt));
+        }
+    }
+
+    println!(
+        "Plausible patches: {} of {} ({} compiled)",
+        plausible.len(),
+        candidates.len(),
+        compiled
+    );
+    for (i, attempt) in &plausible {
+        println!(" {:>4} rank {} {}", i, attempt.rank, attempt.patch);
+    }
+    match plausible.first() {
+        Some((i, attempt)) => println!(
+            "First plausible patch: attempt {} of {}, at suspicious region {}",
+            i,
+            candidates.len(),
+            attempt.rank
+        ),
+        None => println!("No plausible patch in the top {} regions", regions.len()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDED: &str = "fn bump(k: i32, n: i32) -> i32 {
+    if k < n {
+        /*| bump */
+        /*|
+        k + 1
+        */
+        /*|| bump_1 */
+        k + 2
+        /*|| bump_2 */
+        /*|
+        k - 1
+        */
+        /* |*/
+    } else {
+        k
+    }
+}
+";
+
+    fn region(start_line: u32, end_line: u32) -> Region {
+        Region {
+            file: "src/implementation.rs".to_string(),
+            start_line,
+            start_col: 1,
+            end_line,
+            end_col: 80,
+        }
+    }
+
+    #[test]
+    fn test_candidates() {
+        // The block is ranked second, the comparison above it first.
+        assert!(candidates(SEEDED, &[region(20, 30)]).unwrap().is_empty());
+        let patches = candidates(SEEDED, &[region(2, 2), region(8, 8)]).unwrap();
+        let ranks = patches.iter().map(|(rank, _)| *rank).collect::<Vec<_>>();
+        assert!(ranks.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(ranks[0], 1);
+        let siblings = patches
+            .iter()
+            .filter_map(|(rank, patch)| match patch {
+                Patch::Sibling { variant, .. } => Some((*rank, variant.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(siblings, vec![(2, None), (2, Some("bump_2".to_string()))]);
+
+        // Switching back to the base code fixes the seeded fault.
+        let (_, base) = patches.iter().find(|(_, p)| p.to_string().ends_with("base code")).unwrap();
+        let repaired = base.apply(SEEDED).unwrap();
+        let blocks = marauder::parse(&repaired).unwrap();
+        assert_eq!(blocks[0].active(), Some(blocks[0].base()));
+    }
+}
@@ -0,0 +1,353 @@
+//! Building and running a workload's instrumented `faultloc` binary, the
+//! native counterpart of its `steps.sh` and `instrumentation.sh`.
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use faultloc_analysis::coverage::{self, Execution};
+use faultloc_mutants::marauder;
+use faultloc_store::Status;
+
+use crate::experiment::Task;
+
+/// The flags `steps.json` builds with, minus `-C inline-threshold`, which
+/// recent compilers reject; the `llvm-args` form still disables inlining.
+pub const RUSTFLAGS: &str = "-C instrument-coverage -C link-dead-code -C codegen-units=1 -C llvm-args=-inline-threshold=0 -C debuginfo=2";
+
+const SOURCE: &str = "src/implementation.rs";
+const BINARY: &str = "target/release/faultloc";
+
+/// The strategy the trials of a seeded `strategy` are stored as. A seeded
+/// `faultloc` draws its inputs through the harness's own replayable loop, with
+/// its test budget and sizing, rather than through the strategy's testing
+/// library, so its trials are kept apart from those of the library itself.
+pub fn seeded(strategy: &str) -> String {
+    format!("{}-seeded", strategy)
+}
+
+/// How mutations get switched on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Rewrite `src/implementation.rs` and rebuild for every mutation set.
+    Source,
+    /// Build once with the `schemata` feature and pass `--mutant` at runtime.
+    Schemata,
+}
+
+pub struct Run {
+    pub status: Status,
+    pub output: String,
+    pub time: Duration,
+}
+
+pub struct Workload {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Puts the original source back once the build is done, failed or not.
+struct Restore<'a> {
+    path: &'a Path,
+    original: String,
+}
+
+impl Drop for Restore<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::write(self.path, &self.original) {
+            eprintln!("Failed to restore {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Runs `program`, killing it once `timeout` passes.
+fn spawn(mut program: Command, timeout: Duration) -> Result<Run, String> {
+    let start = Instant::now();
+    let mut child = program
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run faultloc: {}", e))?;
+    // Drain stdout on the side so a chatty run cannot block on a full pipe.
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let status = loop {
+        match child.try_wait().map_err(|e| format!("failed to wait for faultloc: {}", e))? {
+            Some(status) if status.success() => break Status::Finished,
+            Some(status) => break Status::Crashed(status.code()),
+            None if start.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Status::TimedOut;
+            },
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    };
+    let time = start.elapsed();
+    let output = reader.join().unwrap_or_default();
+
+    Ok(Run { status, output, time })
+}
+
+/// Looks for an LLVM tool next to rustc first, like `instrumentation.sh`, then
+/// falls back to `PATH`.
+fn llvm_tool(name: &str) -> PathBuf {
+    let sysroot = Command::new("rustc").args(["--print", "sysroot"]).output();
+    let host = Command::new("rustc").arg("-vV").output();
+    if let (Ok(sysroot), Ok(host)) = (sysroot, host) {
+        let sysroot = String::from_utf8_lossy(&sysroot.stdout).trim().to_string();
+        let host = String::from_utf8_lossy(&host.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix("host: ").map(str::to_string));
+        if let Some(host) = host {
+            let tool = Path::new(&sysroot).join("lib/rustlib").join(host).join("bin").join(name);
+            if tool.exists() {
+                return tool;
+            }
+        }
+    }
+    PathBuf::from(name)
+}
+
+fn command(program: &mut Command, what: &str) -> Result<Vec<u8>, String> {
+    let output = program.output().map_err(|e| format!("failed to run {}: {}", what, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed with {}:\n{}",
+            what,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(output.stdout)
+}
+
+impl Workload {
+    pub fn new(root: &Path, language: &str, name: &str) -> Workload {
+        Workload { name: name.to_string(), path: root.join(language).join(name) }
+    }
+
+    /// `rustc -V` as seen from the workload, which may pin its own toolchain.
+    pub fn rustc(&self) -> Result<String, String> {
+        let version = command(Command::new("rustc").arg("-V").current_dir(&self.path), "rustc -V")?;
+        Ok(String::from_utf8_lossy(&version).trim().to_string())
+    }
+
+    /// The source of `src/implementation.rs`, which must have no mutant
+    /// switched on.
+    pub fn source(&self) -> Result<String, String> {
+        let source = self.path.join(SOURCE);
+        let original = std::fs::read_to_string(&source)
+            .map_err(|e| format!("failed to read {}: {}", source.display(), e))?;
+        let blocks =
+            marauder::parse(&original).map_err(|e| format!("{}:{}", source.display(), e))?;
+        if let Some(variant) =
+            blocks.iter().filter_map(|block| block.active()).find(|v| v.name.is_some())
+        {
+            return Err(format!(
+                "{} has mutant {} switched on, restore the base code first",
+                source.display(),
+                variant.name.as_deref().unwrap_or_default()
+            ));
+        }
+        Ok(original)
+    }
+
+    /// Builds the instrumented `faultloc` binary. In [`Mode::Source`] the
+    /// mutations are applied for the duration of the build only.
+    pub fn build(&self, mutations: &[String], mode: Mode) -> Result<(), String> {
+        let source = self.path.join(SOURCE);
+        let original = self.source()?;
+
+        let mut cargo = Command::new("cargo");
+        cargo
+            .args(["build", "--release", "--bin", "faultloc"])
+            .current_dir(&self.path)
+            .env("CARGO_INCREMENTAL", "0")
+            .env("RUSTFLAGS", RUSTFLAGS);
+
+        let _restore = match mode {
+            Mode::Schemata => {
+                cargo.args(["--features", "schemata"]);
+                None
+            },
+            Mode::Source => {
+                let names = mutations.iter().map(String::as_str).collect::<Vec<_>>();
+                let applied = marauder::apply(&original, &names)
+                    .map_err(|e| format!("{}:{}", source.display(), e))?;
+                std::fs::write(&source, applied)
+                    .map_err(|e| format!("failed to write {}: {}", source.display(), e))?;
+                Some(Restore { path: &source, original })
+            },
+        };
+
+        command(&mut cargo, "cargo build").map(|_| ())
+    }
+
+    /// Where [`Workload::build_plain`] builds.
+    pub fn plain_target(&self) -> Result<PathBuf, String> {
+        std::path::absolute(self.path.join("target/plain"))
+            .map_err(|e| format!("failed to locate target/plain: {}", e))
+    }
+
+    /// Builds an uninstrumented `faultloc` from `text` in place of
+    /// `src/implementation.rs`, into its own `target` directory so that it
+    /// does not throw away the instrumented build. The source is restored
+    /// afterwards.
+    pub fn build_plain(&self, text: &str, target: &Path) -> Result<(), String> {
+        let source = self.path.join(SOURCE);
+        let original = self.source()?;
+        std::fs::write(&source, text)
+            .map_err(|e| format!("failed to write {}: {}", source.display(), e))?;
+        let _restore = Restore { path: &source, original };
+
+        command(
+            Command::new("cargo")
+                .args(["build", "--release", "--bin", "faultloc"])
+                .current_dir(&self.path)
+                .env("CARGO_TARGET_DIR", target)
+                .env("CARGO_INCREMENTAL", "0")
+                .env("RUSTFLAGS", ""),
+            "cargo build",
+        )
+        .map(|_| ())
+    }
+
+    /// Runs one trial of `task`, killing `faultloc` once `timeout` passes.
+    /// Snapshots from earlier trials are cleared first.
+    pub fn run(
+        &self,
+        task: &Task,
+        mutations: &[String],
+        mode: Mode,
+        seed: u64,
+        timeout: Duration,
+    ) -> Result<Run, String> {
+        for dir in ["coverage", "profdata", "jsondata"] {
+            let dir = self.path.join(dir);
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir)
+                    .map_err(|e| format!("failed to clear {}: {}", dir.display(), e))?;
+            }
+        }
+
+        // Relative program paths resolve against the child's working directory.
+        let binary = std::path::absolute(self.path.join(BINARY))
+            .map_err(|e| format!("failed to locate faultloc: {}", e))?;
+        let mut faultloc = Command::new(binary);
+        faultloc
+            .args([&task.strategy, &task.property, "--seed", &seed.to_string()])
+            .current_dir(&self.path)
+            .env("LLVM_PROFILE_FILE", "coverage/snapshot_%p-%m.profraw");
+        if mode == Mode::Schemata {
+            for mutant in mutations {
+                faultloc.args(["--mutant", mutant]);
+            }
+        }
+        spawn(faultloc, timeout)
+    }
+
+    /// Runs `faultloc --outcomes` of a [`Workload::build_plain`] build in
+    /// `target` on the inputs of `seed`, for `tests` inputs or the harness
+    /// default.
+    pub fn outcomes(
+        &self,
+        target: &Path,
+        task: &Task,
+        seed: u64,
+        tests: Option<usize>,
+        timeout: Duration,
+    ) -> Result<Run, String> {
+        let binary = std::path::absolute(target.join("release/faultloc"))
+            .map_err(|e| format!("failed to locate faultloc: {}", e))?;
+        let mut faultloc = Command::new(binary);
+        faultloc
+            .args([&task.strategy, &task.property, "--outcomes", "--seed", &seed.to_string()])
+            .current_dir(&self.path)
+            .stderr(Stdio::null());
+        if let Some(tests) = tests {
+            faultloc.args(["--tests", &tests.to_string()]);
+        }
+        spawn(faultloc, timeout)
+    }
+
+    /// Exports every tagged snapshot of the last run into `jsondata/` and
+    /// loads them back. Contrastive snapshots are exported alongside, for
+    /// `faultloc-analysis --contrastive`, but not loaded. The input sizes and
+    /// model divergences the harness noted are copied along, for
+    /// `faultloc-analysis --model size` and the diverging keys of `rank`.
+    pub fn export(&self) -> Result<Vec<Execution>, String> {
+        let coverage = self.path.join("coverage");
+        let profdata = self.path.join("profdata");
+        let jsondata = self.path.join("jsondata");
+        for dir in [&profdata, &jsondata] {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+        }
+        if !coverage.exists() {
+            return Ok(vec![]);
+        }
+
+        let llvm_profdata = llvm_tool("llvm-profdata");
+        let llvm_cov = llvm_tool("llvm-cov");
+        let mut snapshots = std::fs::read_dir(&coverage)
+            .map_err(|e| format!("failed to read {}: {}", coverage.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "profraw")
+                    && path.file_stem().is_some_and(|stem| {
+                        let stem = stem.to_string_lossy();
+                        (stem.starts_with("snapshot_") || stem.starts_with("contrast_"))
+                            && coverage::Outcome::from_stem(&stem).is_some()
+                    })
+            })
+            .collect::<Vec<_>>();
+        snapshots.sort();
+
+        for snapshot in snapshots {
+            let stem = snapshot.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let merged = profdata.join(format!("{}.profdata", stem));
+            command(
+                Command::new(&llvm_profdata)
+                    .args(["merge", "-sparse"])
+                    .arg(&snapshot)
+                    .arg("-o")
+                    .arg(&merged),
+                "llvm-profdata merge",
+            )?;
+            let export = command(
+                Command::new(&llvm_cov)
+                    .arg("export")
+                    .arg(self.path.join(BINARY))
+                    .arg(format!("--instr-profile={}", merged.display()))
+                    .arg("--format=text"),
+                "llvm-cov export",
+            )?;
+            let json = jsondata.join(format!("{}.json", stem));
+            std::fs::write(&json, export)
+                .map_err(|e| format!("failed to write {}: {}", json.display(), e))?;
+        }
+        for entry in std::fs::read_dir(&coverage)
+            .map_err(|e| format!("failed to read {}: {}", coverage.display(), e))?
+        {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if (name.starts_with("sizes_") && name.ends_with(".txt"))
+                || (name.starts_with("divergence_") && name.ends_with(".json"))
+            {
+                std::fs::copy(&path, jsondata.join(&name))
+                    .map_err(|e| format!("failed to copy {}: {}", path.display(), e))?;
+            }
+        }
+
+        coverage::load_dir(&jsondata)
+    }
+}
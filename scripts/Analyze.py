@@ -8,10 +8,20 @@ if __name__ == "__main__":
         lines = f.readlines()
         for line in lines:
             result = json.loads(line)["data"]
+            tests = result.get("tests", {})
+            passed, failed = tests.get("passed", 0), tests.get("failed", 0)
+
+            # The share of the passing (`ep`) and failing (`ef`) inputs that
+            # reached a region, and how much more often the failing ones did
+            def averages(region):
+                positive = region["ep"] / passed if passed else 0
+                negative = region["ef"] / failed if failed else 0
+                return positive, negative, negative - positive
+
             # For each result, count how many regions have positive deltas
             positive_deltas = list(
                 filter(
-                    lambda region: region.get("delta", 0) > 0.01,
+                    lambda region: averages(region)[2] > 0.01,
                     result.get("regions", []),
                 )
             )
@@ -22,8 +32,9 @@ if __name__ == "__main__":
                 # Print the file name, start line:col, end line:col, and positive, negative, delta
                 file = pathlib.Path(region.get("file", "")).name
                 file_line_col = f"{file}:{region['start_line']}:{region['start_col']} - {region['end_line']}:{region['end_col']}"
+                positive, negative, delta = averages(region)
                 print(
-                    f" {file_line_col:<40} | +{round(region['positive_avg'], 2):<5} -{round(region['negative_avg'], 2):<5} Δ{round(region['delta'], 2):<5}"
+                    f" {file_line_col:<40} | +{round(positive, 2):<5} -{round(negative, 2):<5} Δ{round(delta, 2):<5}"
                 )
 
     print(f"Total trials: {len(lines)}")
@@ -0,0 +1,367 @@
+//! The records of `store.jsonl`, one trial of the experiment matrix per line.
+//!
+//! Every line is a [`Record`] stamped with the [`SCHEMA_VERSION`] it was
+//! written with. Bump the version whenever a field changes meaning or goes
+//! away, so that readers refuse records they would misinterpret instead of
+//! guessing at them.
+
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Lines, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Identifies a trial within the experiment matrix.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub workload: String,
+    pub mutations: Vec<String>,
+    /// The strategy, suffixed with `-seeded` for trials whose inputs the
+    /// harness generated from their seed instead of the testing library.
+    pub strategy: String,
+    pub property: String,
+    pub trial: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Finished,
+    TimedOut,
+    /// `faultloc` exited unsuccessfully, with its exit code if it had one.
+    Crashed(Option<i32>),
+}
+
+/// How many property evaluations ended each way.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Tests {
+    pub passed: usize,
+    pub failed: usize,
+    pub discarded: usize,
+}
+
+/// A coverage region with the number of failing (`ef`) and passing (`ep`)
+/// evaluations that executed it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct RegionRecord {
+    pub file: String,
+    pub start_line: u32,
+    pub start_col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+    pub function: Option<String>,
+    pub ef: usize,
+    pub ep: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Trial {
+    #[serde(flatten)]
+    pub key: Key,
+    pub status: Status,
+    /// The generator seed. Records written before every run was seeded have
+    /// none.
+    pub seed: Option<u64>,
+    pub tests: Tests,
+    /// `rustc -V` of the toolchain that built the workload.
+    pub rustc: String,
+    pub rustflags: String,
+    /// Wall time of the `faultloc` run, in seconds.
+    pub time: f64,
+    /// When the run finished, as an RFC 3339 UTC [`timestamp`]. Records
+    /// written before dates were kept have none.
+    #[serde(default)]
+    pub date: Option<String>,
+    /// What `faultloc` printed, including its result line.
+    pub output: String,
+    pub regions: Vec<RegionRecord>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub version: u32,
+    pub data: Trial,
+}
+
+impl Record {
+    pub fn new(data: Trial) -> Record {
+        Record { version: SCHEMA_VERSION, data }
+    }
+}
+
+/// `time` as an RFC 3339 UTC timestamp to the second, such as
+/// `2025-09-22T14:03:51Z`. Timestamps sort as strings in time order.
+pub fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, secs) = ((secs / 86400) as i64, secs % 86400);
+    // Days since the epoch to a civil date, after Howard Hinnant's
+    // `civil_from_days`, counting in 400-year eras from March 0000.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// The JSON Schema every line of the store conforms to.
+pub fn schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(Record)).expect("Failed to serialize the schema")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    /// A line that is not a record, such as one cut short by an interrupted run.
+    Malformed {
+        line: usize,
+        message: String,
+    },
+    /// A record written under another schema version.
+    Version {
+        line: usize,
+        found: u32,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            Error::Malformed { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Version { line, found } => write!(
+                f,
+                "line {}: schema version {} is not supported (expected {})",
+                line, found, SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn io(path: &Path, e: std::io::Error) -> Error {
+    Error::Io { path: path.to_path_buf(), message: e.to_string() }
+}
+
+// Only the version, so a record from another schema is reported as such
+// rather than as whatever field happened to change.
+#[derive(Deserialize)]
+struct Versioned {
+    version: Option<u32>,
+}
+
+fn parse(line: usize, text: &str) -> Result<Record, Error> {
+    let malformed = |e: serde_json::Error| Error::Malformed { line, message: e.to_string() };
+    let versioned = serde_json::from_str::<Versioned>(text).map_err(malformed)?;
+    match versioned.version {
+        Some(SCHEMA_VERSION) => serde_json::from_str(text).map_err(malformed),
+        found => Err(Error::Version { line, found: found.unwrap_or(0) }),
+    }
+}
+
+/// Reads records line by line, numbering lines from 1 and skipping blank ones.
+pub struct Reader {
+    path: PathBuf,
+    lines: Lines<BufReader<File>>,
+    line: usize,
+}
+
+impl Reader {
+    pub fn open(path: &Path) -> Result<Reader, Error> {
+        let file = File::open(path).map_err(|e| io(path, e))?;
+        Ok(Reader { path: path.to_path_buf(), lines: BufReader::new(file).lines(), line: 0 })
+    }
+}
+
+impl Iterator for Reader {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            match self.lines.next()? {
+                Ok(text) if text.trim().is_empty() => continue,
+                Ok(text) => return Some(parse(self.line, &text)),
+                Err(e) => return Some(Err(io(&self.path, e))),
+            }
+        }
+    }
+}
+
+/// Reads every record, failing on the first line that is not one.
+pub fn read(path: &Path) -> Result<Vec<Record>, Error> {
+    Reader::open(path)?.collect()
+}
+
+/// Appends records, flushing each line as it is written.
+pub struct Writer {
+    path: PathBuf,
+    file: File,
+}
+
+impl Writer {
+    pub fn open(path: &Path) -> Result<Writer, Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| io(path, e))?;
+
+        // Start on a fresh line after a record cut short by an interrupted run.
+        let mut last = [b'\n'];
+        if file.seek(SeekFrom::End(-1)).is_ok() {
+            let _ = file.read_exact(&mut last);
+        }
+        if last[0] != b'\n' {
+            writeln!(file).map_err(|e| io(path, e))?;
+        }
+        Ok(Writer { path: path.to_path_buf(), file })
+    }
+
+    pub fn append(&mut self, trial: Trial) -> Result<(), Error> {
+        let line =
+            serde_json::to_string(&Record::new(trial)).expect("Failed to serialize record to JSON");
+        writeln!(self.file, "{}", line).map_err(|e| io(&self.path, e))?;
+        self.file.flush().map_err(|e| io(&self.path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trial(n: usize) -> Trial {
+        Trial {
+            key: Key {
+                workload: "BST".to_string(),
+                mutations: vec!["insert_1".to_string()],
+                strategy: "crabcheck".to_string(),
+                property: "InsertPost".to_string(),
+                trial: n,
+            },
+            status: Status::Finished,
+            seed: Some(42),
+            tests: Tests { passed: 3, failed: 1, discarded: 0 },
+            rustc: "rustc 1.97.0-nightly".to_string(),
+            rustflags: "-C instrument-coverage".to_string(),
+            time: 0.5,
+            date: Some("2025-09-22T14:03:51Z".to_string()),
+            output: "Result: ...".to_string(),
+            regions: vec![RegionRecord {
+                file: "src/implementation.rs".to_string(),
+                start_line: 27,
+                start_col: 1,
+                end_line: 29,
+                end_col: 2,
+                function: Some("bst::implementation::insert".to_string()),
+                ef: 1,
+                ep: 2,
+            }],
+        }
+    }
+
+    fn temp(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("faultloc-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_write_read() {
+        let path = temp("roundtrip");
+        let mut writer = Writer::open(&path).unwrap();
+        writer.append(trial(0)).unwrap();
+        writer.append(trial(1)).unwrap();
+
+        let records = read(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1], Record::new(trial(1)));
+
+        // The trial sits flat under `data`, next to the schema version.
+        let line = std::fs::read_to_string(&path).unwrap();
+        let value: serde_json::Value = serde_json::from_str(line.lines().next().unwrap()).unwrap();
+        assert_eq!(value["version"], SCHEMA_VERSION);
+        assert_eq!(value["data"]["property"], "InsertPost");
+        assert_eq!(value["data"]["regions"][0]["start_line"], 27);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let path = temp("errors");
+        let mut writer = Writer::open(&path).unwrap();
+        writer.append(trial(0)).unwrap();
+        let mut text = std::fs::read_to_string(&path).unwrap();
+        text.push_str("{\"data\": {\"property\": \"InsertPost\"}}\n");
+        text.push_str("{\"version\": 99, \"data\": {}}\n\n");
+        text.push_str("{\"version\": 1, \"da");
+        std::fs::write(&path, text).unwrap();
+
+        let results = Reader::open(&path).unwrap().collect::<Vec<_>>();
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert_eq!(results[1], Err(Error::Version { line: 2, found: 0 }));
+        assert_eq!(results[2], Err(Error::Version { line: 3, found: 99 }));
+        assert!(matches!(results[3], Err(Error::Malformed { line: 5, .. })));
+        assert!(read(&path).is_err());
+
+        // Appending after the cut short line starts a fresh one.
+        Writer::open(&path).unwrap().append(trial(1)).unwrap();
+        let last = Reader::open(&path).unwrap().last().unwrap();
+        assert_eq!(last.unwrap().data.key.trial, 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = schema();
+        let properties = &schema["properties"];
+        assert!(properties["version"].is_object());
+        assert!(properties["data"].is_object());
+        let trial = &schema["$defs"]["Trial"];
+        for field in ["workload", "strategy", "seed", "tests", "rustc", "rustflags", "time", "date"]
+        {
+            assert!(trial["properties"][field].is_object(), "{}", field);
+        }
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let at = |secs| timestamp(UNIX_EPOCH + std::time::Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_758_549_831), "2025-09-22T14:03:51Z");
+        assert_eq!(at(4_107_542_399), "2100-02-28T23:59:59Z");
+
+        // Records from before dates were kept still read.
+        let mut record = serde_json::to_value(Record::new(trial(0))).unwrap();
+        record["data"].as_object_mut().unwrap().remove("date");
+        let record = parse(1, &record.to_string()).unwrap();
+        assert_eq!(record.data.date, None);
+    }
+}
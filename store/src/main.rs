@@ -0,0 +1,54 @@
+use std::{path::Path, process::ExitCode};
+
+use faultloc_store::{Reader, SCHEMA_VERSION};
+
+fn usage(program: &str) {
+    eprintln!("Usage: {} schema", program);
+    eprintln!("       {} check <store.jsonl>", program);
+}
+
+fn check(path: &str) -> Result<(), String> {
+    let mut records = 0;
+    let mut errors = 0;
+    for record in Reader::open(Path::new(path)).map_err(|e| e.to_string())? {
+        match record {
+            Ok(_) => records += 1,
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                errors += 1;
+            },
+        }
+    }
+    println!(
+        "{}: {} records at schema version {}, {} errors",
+        path, records, SCHEMA_VERSION, errors
+    );
+    if errors > 0 { Err(format!("{} lines do not match the schema", errors)) } else { Ok(()) }
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    let result = match args.get(1).map(String::as_str) {
+        Some("schema") => {
+            let schema = serde_json::to_string_pretty(&faultloc_store::schema())
+                .expect("Failed to serialize the schema");
+            println!("{}", schema);
+            Ok(())
+        },
+        Some("check") => match args.get(2) {
+            Some(path) => check(path),
+            None => Err("Missing store file".to_string()),
+        },
+        Some(command) => Err(format!("Unknown command: {}", command)),
+        None => Err("Missing command".to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            usage(&args[0]);
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -1,9 +1,12 @@
 export PATH="$(rustc --print sysroot)/lib/rustlib/$(rustc -vV | grep host | cut -d' ' -f2)/bin:$PATH"
 
+# Contrastive snapshots of `faultloc --neighbors` go through the same steps,
+# for `faultloc-analysis rank --contrastive`.
+shopt -s nullglob
 mkdir -p profdata
-FILES=(coverage/snapshot_*.profraw)
-if [ "${#FILES[@]}" -eq 0 ] || [ "${FILES[0]}" == "snapshot_*.profraw" ]; then
-    echo "❌ No snapshot_*.profraw files found!"
+FILES=(coverage/snapshot_*.profraw coverage/contrast_*.profraw)
+if [ "${#FILES[@]}" -eq 0 ]; then
+    echo "❌ No snapshot_*.profraw or contrast_*.profraw files found!"
     exit 1
 fi
 
@@ -16,7 +19,7 @@ for f in "${FILES[@]}"; do
     llvm-profdata merge -sparse "$f" -o "profdata/${file_name%.profraw}.profdata"
 done
 
-FILES=(profdata/snapshot_*.profdata)
+FILES=(profdata/snapshot_*.profdata profdata/contrast_*.profdata)
 mkdir -p jsondata
 mkdir -p jsondata/demangled
 for FILE in "${FILES[@]}"; do
@@ -25,4 +28,8 @@ for FILE in "${FILES[@]}"; do
     #  Now run `demangler` to demangle the symbols
     demangler jsondata/"$(basename $FILE .profdata)".json jsondata/demangled/"$(basename $FILE .profdata)".json
 done
+# The input size of each snapshot, for `faultloc-analysis --model size`.
+cp coverage/sizes_*.txt jsondata/demangled/ 2>/dev/null || true
+# The model divergence of each recorded failure, for `faultloc-analysis rank`.
+cp coverage/divergence_*.json jsondata/demangled/ 2>/dev/null || true
 echo "✅ Exported to jsondata/*.json"
\ No newline at end of file
@@ -24,6 +24,7 @@ use Tree::*;
 const FUEL: usize = 10000;
 
 // Insert
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn insert(k: i32, v: i32, t: Tree) -> Tree {
     match t {
         E => T(Box::new(E), k, v, Box::new(E)),
@@ -64,17 +65,22 @@ pub(crate) fn insert(k: i32, v: i32, t: Tree) -> Tree {
 }
 
 // Join
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn join(l: Tree, r: Tree) -> Tree {
-    match (l, r) {
+    let t = match (l, r) {
         (E, r) => r,
         (l, E) => l,
         (T(l1, k1, v1, r1), T(l2, k2, v2, r2)) => {
             T(l1, k1, v1, Box::new(T(Box::new(join(*r1, *l2)), k2, v2, r2)))
         },
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("join", &t);
+    t
 }
 
 // Delete
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn delete(k: i32, t: Tree) -> Tree {
     match t {
         E => E,
@@ -114,8 +120,9 @@ pub(crate) fn delete(k: i32, t: Tree) -> Tree {
 }
 
 // Below
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn below(k: i32, t: Tree) -> Tree {
-    match t {
+    let t = match t {
         E => E,
         T(l, k2, v2, r) => {
             if k <= k2 {
@@ -124,12 +131,16 @@ pub(crate) fn below(k: i32, t: Tree) -> Tree {
                 T(l, k2, v2, Box::new(below(k, *r)))
             }
         },
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("below", &t);
+    t
 }
 
 // Above
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn above(k: i32, t: Tree) -> Tree {
-    match t {
+    let t = match t {
         E => E,
         T(l, k2, v2, r) => {
             if k2 <= k {
@@ -138,10 +149,14 @@ pub(crate) fn above(k: i32, t: Tree) -> Tree {
                 T(Box::new(above(k, *l)), k2, v2, r)
             }
         },
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("above", &t);
+    t
 }
 
 // Union with fuel
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn union_(l: Tree, r: Tree, f: usize) -> Tree {
     if f == 0 {
         return E;
@@ -214,11 +229,13 @@ pub(crate) fn union_(l: Tree, r: Tree, f: usize) -> Tree {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn union(l: Tree, r: Tree) -> Tree {
     union_(l, r, FUEL)
 }
 
 // Find
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn find(k: i32, t: &Tree) -> Option<i32> {
     match t {
         E => None,
@@ -1,5 +1,43 @@
 #![feature(box_patterns)]
 
+#[cfg(not(feature = "schemata"))]
 pub mod implementation;
+#[cfg(feature = "schemata")]
+pub mod implementation {
+    include!(concat!(env!("OUT_DIR"), "/src/implementation.rs"));
+}
+pub mod mutants;
+pub mod properties;
 pub mod spec;
 pub mod strategies;
+
+/// The workload, as the harness binaries run it.
+pub struct Bst;
+
+impl faultloc_harness::Workload for Bst {
+    const CHECKED: bool = cfg!(feature = "checked");
+    const FAULTLOC_TESTS: usize = 200;
+    const NAME: &'static str = "bst";
+    const PROPERTIES: &'static [(&'static str, &'static str)] = properties::PROPERTIES;
+    const TESTS: u64 = 200_00;
+
+    fn dispatch<R: faultloc_harness::Runner>(name: &str, runner: R) -> Option<R::Output> {
+        properties::dispatch(name, runner)
+    }
+
+    fn mutants() -> &'static [&'static str] {
+        mutants::MUTANTS
+    }
+
+    fn select(names: &[String]) -> Result<(), String> {
+        mutants::select(names)
+    }
+
+    fn selected() -> &'static [String] {
+        mutants::selected()
+    }
+
+    fn source() -> Option<&'static str> {
+        mutants::source()
+    }
+}
@@ -0,0 +1,65 @@
+//! The properties of the workload, shared by all of its binaries.
+
+use {
+    crate::{
+        implementation::Tree,
+        spec,
+    },
+    serde::de::DeserializeOwned,
+};
+
+fn parse<A: DeserializeOwned>(tests: &str) -> Result<Vec<A>, String> {
+    serde_lexpr::from_str(tests).map_err(|e| e.to_string())
+}
+
+faultloc_harness::properties! {
+    parse = parse;
+    InsertValid => prop_insert_valid(t: Tree, k: i32, v: i32),
+    DeleteValid => prop_delete_valid(t: Tree, k: i32),
+    UnionValid => prop_union_valid(t1: Tree, t2: Tree),
+    InsertPost => prop_insert_post(t: Tree, k1: i32, k2: i32, v: i32),
+    DeletePost => prop_delete_post(t: Tree, k1: i32, k2: i32),
+    UnionPost => prop_union_post(t1: Tree, t2: Tree, k: i32),
+    InsertModel => prop_insert_model(t: Tree, k: i32, v: i32),
+    DeleteModel => prop_delete_model(t: Tree, k: i32),
+    UnionModel => prop_union_model(t1: Tree, t2: Tree),
+    InsertInsert => prop_insert_insert(t: Tree, k1: i32, k2: i32, v1: i32, v2: i32),
+    InsertDelete => prop_insert_delete(t: Tree, k1: i32, k2: i32, v: i32),
+    InsertUnion => prop_insert_union(t1: Tree, t2: Tree, k1: i32, k2: i32),
+    DeleteInsert => prop_delete_insert(t: Tree, k1: i32, k2: i32, v: i32),
+    DeleteDelete => prop_delete_delete(t: Tree, k1: i32, k2: i32),
+    DeleteUnion => prop_delete_union(t1: Tree, t2: Tree, k: i32),
+    UnionDeleteInsert => prop_union_delete_insert(t1: Tree, t2: Tree, k1: i32, k2: i32),
+    UnionUnionIdempotent => prop_union_union_idempotent(t: Tree),
+    UnionUnionAssoc => prop_union_union_assoc(t1: Tree, t2: Tree, t3: Tree),
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        faultloc_harness::{
+            Input,
+            Property,
+            Runner,
+        },
+    };
+
+    struct Name;
+
+    impl Runner for Name {
+        type Output = &'static str;
+
+        fn run<A: Input>(self, property: Property<A>) -> &'static str {
+            property.name
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        for (name, _) in PROPERTIES {
+            assert_eq!(dispatch(name, Name), Some(*name));
+        }
+        assert_eq!(dispatch("insert_valid", Name), None);
+    }
+}
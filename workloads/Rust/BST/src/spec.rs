@@ -1,4 +1,5 @@
 use etna_rs_utils::Implies as _;
+use faultloc_harness::divergence;
 
 use crate::implementation::{Tree, delete, find, insert, union};
 
@@ -25,7 +26,7 @@ where
     xs.iter().all(f)
 }
 
-fn is_bst(t: &Tree) -> bool {
+pub(crate) fn is_bst(t: &Tree) -> bool {
     match t {
         Tree::E => true,
         Tree::T(l, k, _, r) => {
@@ -34,6 +35,12 @@ fn is_bst(t: &Tree) -> bool {
     }
 }
 
+/// With the `checked` feature, checks a tree an internal function returned.
+#[cfg(feature = "checked")]
+pub(crate) fn checkpoint(function: &'static str, t: &Tree) {
+    faultloc_harness::checkpoint::check(function, t, is_bst);
+}
+
 fn to_list(t: &Tree) -> Vec<(i32, i32)> {
     match t {
         Tree::E => vec![],
@@ -136,19 +143,22 @@ pub fn prop_union_post(t1: Tree, t2: Tree, k: i32) -> Option<bool> {
 
 pub fn prop_insert_model(t: Tree, k: i32, v: i32) -> Option<bool> {
     is_bst(&t).implies(|| {
-        to_list(&insert(k, v, t.clone())) == l_insert((k, v), &delete_key(k, &to_list(&t)))
+        let expected = l_insert((k, v), &delete_key(k, &to_list(&t)));
+        divergence::compare(&expected, &to_list(&insert(k, v, t.clone())))
     })
 }
 
 pub fn prop_delete_model(t: Tree, k: i32) -> Option<bool> {
-    is_bst(&t).implies(|| to_list(&delete(k, t.clone())) == delete_key(k, &to_list(&t)))
+    is_bst(&t).implies(|| {
+        divergence::compare(&delete_key(k, &to_list(&t)), &to_list(&delete(k, t.clone())))
+    })
 }
 
 pub fn prop_union_model(t1: Tree, t2: Tree) -> Option<bool> {
     is_bst(&t1).implies(|| {
         is_bst(&t2).implies(|| {
-            to_list(&union(t1.clone(), t2.clone()))
-                == l_sort(&l_union_by(|x, _| x, &to_list(&t1), &to_list(&t2)))
+            let expected = l_sort(&l_union_by(|x, _| x, &to_list(&t1), &to_list(&t2)));
+            divergence::compare(&expected, &to_list(&union(t1.clone(), t2.clone())))
         })
     })
 }
@@ -3,10 +3,20 @@ use {
         Arbitrary,
         Mutate,
     },
+    faultloc_harness::{
+        shrink::Shrink,
+        size::Size,
+    },
     rand::Rng,
 };
 
-use crate::implementation::Tree;
+use crate::{
+    implementation::{
+        Tree,
+        size,
+    },
+    spec::is_bst,
+};
 
 fn gen_tree<R: Rng>(r: &mut R, size: u32, lo: i32, hi: i32) -> Tree {
     if size == 0 || hi - lo <= 1 {
@@ -73,3 +83,59 @@ impl<R: Rng> Mutate<R> for Tree {
         mut_tree(rng, self, n, i32::MIN, i32::MAX)
     }
 }
+
+fn shrink_tree(t: &Tree) -> Vec<Tree> {
+    match t {
+        Tree::E => vec![],
+        Tree::T(l, k, v, r) => {
+            let mut candidates = vec![Tree::E, *l.clone(), *r.clone()];
+            candidates.extend(
+                shrink_tree(l).into_iter().map(|l| Tree::T(Box::new(l), *k, *v, r.clone())),
+            );
+            candidates.extend(
+                shrink_tree(r).into_iter().map(|r| Tree::T(l.clone(), *k, *v, Box::new(r))),
+            );
+            candidates.extend(k.shrink().into_iter().map(|k| Tree::T(l.clone(), k, *v, r.clone())));
+            candidates.extend(v.shrink().into_iter().map(|v| Tree::T(l.clone(), *k, v, r.clone())));
+            candidates
+        },
+    }
+}
+
+impl Shrink for Tree {
+    fn shrink(&self) -> Vec<Self> {
+        // Dropping subtrees keeps the order of the keys left, shrinking a key
+        // may not.
+        shrink_tree(self).into_iter().filter(is_bst).collect()
+    }
+}
+
+impl Size for Tree {
+    fn size(&self) -> usize {
+        size(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        rand::{
+            SeedableRng,
+            rngs::StdRng,
+        },
+    };
+
+    #[test]
+    fn test_shrink_keeps_bst() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in 1..30 {
+            let t = Tree::generate(&mut rng, n * 4);
+            for candidate in t.shrink() {
+                assert!(is_bst(&candidate), "{} shrank to {}", t, candidate);
+                assert!(size(&candidate) <= size(&t));
+                assert_ne!(candidate, t);
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! With the `schemata` feature, compiles every marauder mutant of
+//! `src/implementation.rs` into the crate behind a runtime switch.
+
+use std::{env, fs, path::PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/implementation.rs");
+    if env::var_os("CARGO_FEATURE_SCHEMATA").is_none() {
+        return;
+    }
+
+    let source = fs::read_to_string("src/implementation.rs")
+        .expect("Failed to read src/implementation.rs");
+    let schemata = faultloc_mutants::schemata::rewrite(&source)
+        .unwrap_or_else(|e| panic!("src/implementation.rs:{}", e));
+
+    // Kept under `src/` so coverage regions still end in `src/implementation.rs`.
+    let dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("src");
+    fs::create_dir_all(&dir).expect("Failed to create the schemata directory");
+    let path = dir.join("implementation.rs");
+    fs::write(&path, schemata).expect("Failed to write the schemata");
+    println!("cargo:rustc-env=FAULTLOC_SCHEMATA_SOURCE={}", path.display());
+}
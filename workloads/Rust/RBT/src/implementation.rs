@@ -63,6 +63,7 @@ pub(crate) fn elems(t: &Tree) -> Vec<(i32, i32)> {
     kvs
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn blacken(t: Tree) -> Tree {
     match t {
         E => E,
@@ -70,6 +71,7 @@ pub(crate) fn blacken(t: Tree) -> Tree {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn redden(t: Tree) -> Option<Tree> {
     match t {
         T(B, a, x, vx, b) => Some(T(R, a, x, vx, b)),
@@ -77,8 +79,9 @@ pub(crate) fn redden(t: Tree) -> Option<Tree> {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn balance(col: Color, tl: Tree, key: i32, val: i32, tr: Tree) -> Tree {
-    match (col, tl, key, val, tr) {
+    let t = match (col, tl, key, val, tr) {
         /*| */
         (B, T(R, box T(R, a, x, vx, b), y, vy, c), z, vz, d) => T(
             R,
@@ -132,10 +135,15 @@ pub(crate) fn balance(col: Color, tl: Tree, key: i32, val: i32, tr: Tree) -> Tre
             Box::new(T(B, c, z, vz, d)),
         ),
         (rb, a, x, vx, b) => T(rb, Box::new(a), x, vx, Box::new(b)),
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("balance", &t);
+    t
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn insert(key: i32, val: i32, t: Tree) -> Tree {
+    #[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
     fn ins(x: i32, vx: i32, s: Tree) -> Tree {
         match (x, vx, s) {
             (x, vx, E) => {
@@ -205,8 +213,9 @@ pub(crate) fn insert(key: i32, val: i32, t: Tree) -> Tree {
     blacken(ins(key, val, t))
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn bal_left(tl: Tree, k: i32, v: i32, tr: Tree) -> Option<Tree> {
-    match (tl, k, v, tr) {
+    let t = match (tl, k, v, tr) {
         (T(R, a, x, vx, b), y, vy, c) => {
             Some(T(R, Box::new(T(B, a, x, vx, b)), y, vy, Box::new(c)))
         }
@@ -234,11 +243,15 @@ pub(crate) fn bal_left(tl: Tree, k: i32, v: i32, tr: Tree) -> Option<Tree> {
             /* |*/
         }
         (_, _, _, _) => None,
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("bal_left", t.as_ref());
+    t
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn bal_right(tl: Tree, k: i32, v: i32, tr: Tree) -> Option<Tree> {
-    match (tl, k, v, tr) {
+    let t = match (tl, k, v, tr) {
         (a, x, vx, T(R, b, y, vy, c)) => {
             Some(T(R, Box::new(a), x, vx, Box::new(T(B, b, y, vy, c))))
         }
@@ -266,15 +279,19 @@ pub(crate) fn bal_right(tl: Tree, k: i32, v: i32, tr: Tree) -> Option<Tree> {
             /* |*/
         }
         (_, _, _, _) => None,
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("bal_right", t.as_ref());
+    t
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn _join(t1: Tree, t2: Tree, f: usize) -> Option<Tree> {
     if f == 0 {
         return None;
     }
     let fp = f - 1;
-    match (t1, t2) {
+    let t = match (t1, t2) {
         (E, a) => Some(a),
         (a, E) => Some(a),
         (T(R, a, x, vx, box b), T(R, box c, y, vy, d)) => match _join(b, c, fp) {
@@ -335,20 +352,25 @@ pub(crate) fn _join(t1: Tree, t2: Tree, f: usize) -> Option<Tree> {
             let tp = _join(b, c, fp)?;
             Some(T(R, a, x, vx, Box::new(tp)))
         }
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("_join", t.as_ref());
+    t
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn join(t1: Tree, t2: Tree) -> Option<Tree> {
     _join(t1, t2, FUEL)
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn del(x: i32, s: Tree, f: usize) -> Option<Tree> {
     if f == 0 {
         return None;
     }
     let fp = f - 1;
 
-    match s {
+    let t = match s {
         E => Some(E),
         T(_, box a, y, vy, box b) => {
             /*| */
@@ -384,9 +406,13 @@ pub(crate) fn del(x: i32, s: Tree, f: usize) -> Option<Tree> {
             */
             /* |*/
         }
-    }
+    };
+    #[cfg(feature = "checked")]
+    crate::spec::checkpoint("del", t.as_ref());
+    t
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 fn del_left(x: i32, dl: Tree, dy: i32, dvy: i32, dr: Tree, f: usize) -> Option<Tree> {
     if f == 0 {
         return None;
@@ -406,6 +432,7 @@ fn del_left(x: i32, dl: Tree, dy: i32, dvy: i32, dr: Tree, f: usize) -> Option<T
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 fn del_right(x: i32, dl: Tree, dy: i32, dvy: i32, dr: Tree, f: usize) -> Option<Tree> {
     if f == 0 {
         return None;
@@ -425,6 +452,7 @@ fn del_right(x: i32, dl: Tree, dy: i32, dvy: i32, dr: Tree, f: usize) -> Option<
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn delete(x: i32, t: Tree) -> Option<Tree> {
     /*| */
     let tp = del(x, t, FUEL)?;
@@ -436,6 +464,7 @@ pub(crate) fn delete(x: i32, t: Tree) -> Option<Tree> {
     /* |*/
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub(crate) fn find(x: i32, t: Tree) -> Option<i32> {
     match t {
         E => None,
@@ -0,0 +1,57 @@
+//! The properties of the workload, shared by all of its binaries.
+
+use {
+    crate::{
+        implementation::Tree,
+        spec,
+    },
+    serde::de::DeserializeOwned,
+};
+
+fn parse<A: DeserializeOwned>(tests: &str) -> Result<Vec<A>, String> {
+    serde_lexpr::from_str(tests).map_err(|e| e.to_string())
+}
+
+faultloc_harness::properties! {
+    parse = parse;
+    InsertValid => prop_insert_valid(t: Tree, k: i32, v: i32),
+    DeleteValid => prop_delete_valid(t: Tree, k: i32),
+    InsertPost => prop_insert_post(t: Tree, k1: i32, k2: i32, v: i32),
+    DeletePost => prop_delete_post(t: Tree, k1: i32, k2: i32),
+    InsertModel => prop_insert_model(t: Tree, k: i32, v: i32),
+    DeleteModel => prop_delete_model(t: Tree, k: i32),
+    InsertInsert => prop_insert_insert(t: Tree, k1: i32, k2: i32, v1: i32, v2: i32),
+    InsertDelete => prop_insert_delete(t: Tree, k1: i32, k2: i32, v: i32),
+    DeleteInsert => prop_delete_insert(t: Tree, k1: i32, k2: i32, v: i32),
+    DeleteDelete => prop_delete_delete(t: Tree, k1: i32, k2: i32),
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        faultloc_harness::{
+            Input,
+            Property,
+            Runner,
+        },
+    };
+
+    struct Name;
+
+    impl Runner for Name {
+        type Output = &'static str;
+
+        fn run<A: Input>(self, property: Property<A>) -> &'static str {
+            property.name
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        for (name, _) in PROPERTIES {
+            assert_eq!(dispatch(name, Name), Some(*name));
+        }
+        assert_eq!(dispatch("insert_valid", Name), None);
+    }
+}
@@ -1,64 +1,85 @@
 use crate::implementation::{Color, Tree, delete, find, insert};
 
 use etna_rs_utils::Implies as _;
+use faultloc_harness::{
+    divergence,
+    verdict::{self, Verdict},
+};
 
-pub(crate) fn is_bst(t: &Tree) -> bool {
-    fn every(p: &dyn Fn(i32) -> bool, t: &Tree) -> bool {
-        match t {
-            Tree::E => true,
-            Tree::T(_, box a, x, _, box b) => every(&p, a) && p(*x) && every(&p, b),
-        }
-    }
+/// The path of the first node whose key is out of the bounds its ancestors
+/// set. Repeated keys are out of bounds too.
+fn unordered(t: &Tree, lo: Option<i32>, hi: Option<i32>) -> Option<String> {
     match t {
-        Tree::E => true,
+        Tree::E => None,
         Tree::T(_, a, x, _, b) => {
-            // -- Difference from SC: donpt allow repeated keys.
-            every(&|y| y < *x, a) && every(&|y| y > *x, b) && is_bst(a) && is_bst(b)
+            if lo.is_some_and(|lo| *x <= lo) || hi.is_some_and(|hi| *x >= hi) {
+                return Some(String::new());
+            }
+            unordered(a, lo, Some(*x))
+                .map(|path| format!("L{}", path))
+                .or_else(|| unordered(b, Some(*x), hi).map(|path| format!("R{}", path)))
         }
     }
 }
 
-pub(crate) fn no_red_red(t: &Tree) -> bool {
-    fn black_root(t: &Tree) -> bool {
-        match t {
-            Tree::T(Color::R, _, _, _, _) => false,
-            _ => true,
-        }
-    }
+/// The path of the first red node with a red child.
+fn red_red(t: &Tree) -> Option<String> {
+    let red = |t: &Tree| matches!(t, Tree::T(Color::R, ..));
     match t {
-        Tree::E => true,
-        Tree::T(Color::B, a, _, _, b) => no_red_red(a) && no_red_red(b),
-        Tree::T(Color::R, a, _, _, b) => {
-            black_root(a) && black_root(b) && no_red_red(a) && no_red_red(b)
+        Tree::E => None,
+        Tree::T(rb, a, _, _, b) => {
+            if *rb == Color::R && (red(a) || red(b)) {
+                return Some(String::new());
+            }
+            red_red(a)
+                .map(|path| format!("L{}", path))
+                .or_else(|| red_red(b).map(|path| format!("R{}", path)))
         }
     }
 }
 
-pub(crate) fn consistent_black_height(t: &Tree) -> bool {
-    fn go(t: &Tree) -> (bool, i32) {
-        match t {
-            Tree::E => (true, 1),
-            Tree::T(rb, a, _, _, b) => {
-                let (a_bool, a_height) = go(a);
-                let (b_bool, b_height) = go(b);
-                let is_black = |rb| match rb {
-                    Color::B => 1,
-                    Color::R => 0,
-                };
-                (
-                    a_bool && b_bool && a_height == b_height,
-                    a_height + is_black(*rb),
-                )
+/// The black height of `t`, or the path of the lowest, leftmost node whose
+/// subtrees differ in it.
+fn black_height(t: &Tree) -> Result<i32, String> {
+    match t {
+        Tree::E => Ok(1),
+        Tree::T(rb, a, _, _, b) => {
+            let a_height = black_height(a).map_err(|path| format!("L{}", path))?;
+            let b_height = black_height(b).map_err(|path| format!("R{}", path))?;
+            if a_height != b_height {
+                return Err(String::new());
             }
+            Ok(a_height + if *rb == Color::B { 1 } else { 0 })
         }
     }
+}
 
-    let (a_bool, _) = go(&t);
-    a_bool
+/// Which red-black tree invariants `t` breaks, each with the first subtree
+/// that breaks it.
+pub(crate) fn verdict(t: &Tree) -> Verdict {
+    let mut verdict = Verdict::default();
+    verdict.check("bst", unordered(t, None, None));
+    verdict.check("no_red_red", red_red(t));
+    verdict.check("black_height", black_height(t).err());
+    verdict
 }
 
+/// Whether `t` is a red-black tree. A broken verdict is kept for `faultloc`
+/// to report.
 pub(crate) fn is_rbt(t: &Tree) -> Option<bool> {
-    Some(is_bst(t) && consistent_black_height(t) && no_red_red(t))
+    Some(verdict::keep(verdict(t)))
+}
+
+/// With the `checked` feature, checks a tree an internal function returned.
+/// Insertion and deletion leave red-red violations at the root for the caller
+/// to fix, so only ordering and black height are checked.
+#[cfg(feature = "checked")]
+pub(crate) fn checkpoint<'a>(function: &'static str, t: impl Into<Option<&'a Tree>>) {
+    if let Some(t) = t.into() {
+        faultloc_harness::checkpoint::check(function, t, |t| {
+            unordered(t, None, None).is_none() && black_height(t).is_ok()
+        });
+    }
 }
 
 pub(crate) fn to_list(t: &Tree) -> Vec<(i32, i32)> {
@@ -120,12 +141,16 @@ pub(crate) fn l_insert(kv: (i32, i32), l: &[(i32, i32)]) -> Vec<(i32, i32)> {
 
 pub fn prop_insert_model(t: Tree, k: i32, v: i32) -> Option<bool> {
     is_rbt(&t).implies(|| {
-        to_list(&insert(k, v, t.clone())) == l_insert((k, v), &delete_key(k, &to_list(&t)))
+        let expected = l_insert((k, v), &delete_key(k, &to_list(&t)));
+        divergence::compare(&expected, &to_list(&insert(k, v, t.clone())))
     })
 }
 
 pub fn prop_delete_model(t: Tree, k: i32) -> Option<bool> {
-    is_rbt(&t).implies(|| Some(to_list(&delete(k, t.clone())?) == delete_key(k, &to_list(&t))))
+    is_rbt(&t).implies(|| {
+        let actual = to_list(&delete(k, t.clone())?);
+        Some(divergence::compare(&delete_key(k, &to_list(&t)), &actual))
+    })
 }
 
 pub fn prop_insert_insert(t: Tree, k: i32, kp: i32, v: i32, vp: i32) -> Option<bool> {
@@ -178,3 +203,56 @@ pub fn prop_delete_delete(t: Tree, k: i32, kp: i32) -> Option<bool> {
         },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(c: Color, l: Tree, k: i32, r: Tree) -> Tree {
+        Tree::T(c, Box::new(l), k, 0, Box::new(r))
+    }
+
+    #[test]
+    fn test_verdict() {
+        use Color::{B, R};
+        use Tree::E;
+
+        let valid = node(B, node(R, E, 1, E), 2, node(R, E, 3, E));
+        assert!(verdict(&valid).holds());
+
+        // What `swap_cd` leaves behind: a key out of order, deep down.
+        let swapped = node(B, node(B, E, 1, node(R, E, 4, E)), 2, node(B, E, 3, E));
+        assert_eq!(verdict(&swapped).to_string(), "bst at LR");
+
+        // What `miscolor_insert` leaves behind: a black leaf too many.
+        let miscolored = node(B, E, 1, node(R, E, 2, node(B, E, 3, E)));
+        assert_eq!(verdict(&miscolored).to_string(), "black_height at R");
+
+        let red_red = node(B, node(R, node(R, E, 1, E), 2, E), 3, node(B, E, 5, E));
+        assert_eq!(verdict(&red_red).to_string(), "no_red_red at L, black_height at root");
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn test_checkpoints() {
+        use faultloc_harness::checkpoint;
+
+        // Every intermediate tree of a correct insertion and deletion is valid.
+        let keys = (0..64).map(|i| i * 37 % 64).collect::<Vec<_>>();
+        checkpoint::reset();
+        let mut t = Tree::E;
+        for &k in &keys {
+            t = insert(k, k, t);
+        }
+        for &k in keys.iter().rev() {
+            t = delete(k, t).unwrap();
+        }
+        assert_eq!(t, Tree::E);
+        assert_eq!(checkpoint::take(), None);
+
+        let unordered = node(Color::R, Tree::E, 1, node(Color::R, Tree::E, 0, Tree::E));
+        checkpoint("balance", &unordered);
+        let violation = checkpoint::take().unwrap();
+        assert_eq!((violation.function, violation.call), ("balance", 1));
+    }
+}
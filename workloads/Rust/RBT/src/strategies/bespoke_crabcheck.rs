@@ -10,11 +10,17 @@ use {
         },
         blacken,
         elems,
+        size,
     },
+    crate::spec::is_rbt,
     crabcheck::quickcheck::{
         Arbitrary,
         Mutate,
     },
+    faultloc_harness::{
+        shrink::Shrink,
+        size::Size,
+    },
     rand::Rng,
 };
 
@@ -90,3 +96,69 @@ impl<R: Rng> Mutate<R> for Tree {
         kvs.iter().fold(E, |t, (k, v)| insert(*k, *v, t))
     }
 }
+
+fn shrink_values(t: &Tree) -> Vec<Tree> {
+    match t {
+        E => vec![],
+        T(c, l, k, v, r) => {
+            let mut candidates: Vec<Tree> =
+                v.shrink().into_iter().map(|v| T(*c, l.clone(), *k, v, r.clone())).collect();
+            candidates.extend(
+                shrink_values(l).into_iter().map(|l| T(*c, Box::new(l), *k, *v, r.clone())),
+            );
+            candidates.extend(
+                shrink_values(r).into_iter().map(|r| T(*c, l.clone(), *k, *v, Box::new(r))),
+            );
+            candidates
+        },
+    }
+}
+
+impl Shrink for Tree {
+    fn shrink(&self) -> Vec<Self> {
+        if *self == E {
+            return vec![];
+        }
+        // Removing a binding rebuilds the tree through `insert` above, as the
+        // generator does, rather than through the implementation under test.
+        let kvs = elems(self);
+        let mut candidates = vec![E];
+        for i in 0..kvs.len() {
+            let kvs = kvs.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, kv)| kv);
+            candidates.push(kvs.fold(E, |t, (k, v)| insert(*k, *v, t)));
+        }
+        candidates.extend(shrink_values(self));
+        candidates.retain(|t| is_rbt(t) == Some(true));
+        candidates
+    }
+}
+
+impl Size for Tree {
+    fn size(&self) -> usize {
+        size(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        rand::{
+            SeedableRng,
+            rngs::StdRng,
+        },
+    };
+
+    #[test]
+    fn test_shrink_keeps_rbt() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in 1..50 {
+            let t = bespoke(n, &mut rng);
+            for candidate in t.shrink() {
+                assert_eq!(is_rbt(&candidate), Some(true), "{} shrank to {}", t, candidate);
+                assert!(elems(&candidate).len() <= elems(&t).len());
+                assert_ne!(candidate, t);
+            }
+        }
+    }
+}
@@ -52,6 +52,7 @@ pub type Ctx = Vec<Typ>;
 use Expr::*;
 use Typ::*;
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn get_typ(ctx: &Ctx, expr: &Expr) -> Option<Typ> {
     match expr {
         Var(i) => {
@@ -78,6 +79,7 @@ pub fn get_typ(ctx: &Ctx, expr: &Expr) -> Option<Typ> {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn type_check(ctx: &Ctx, expr: &Expr, typ: &Typ) -> bool {
     match get_typ(ctx, expr) {
         Some(expr_typ) => expr_typ == *typ,
@@ -85,6 +87,7 @@ pub fn type_check(ctx: &Ctx, expr: &Expr, typ: &Typ) -> bool {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn shift(d: i32, expr: &Expr) -> Expr {
     fn go(c: i32, e: &Expr, d: i32) -> Expr {
         match e {
@@ -123,6 +126,7 @@ pub fn shift(d: i32, expr: &Expr) -> Expr {
     go(0, expr, d)
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn subst(n: i32, s: &Expr, e: &Expr) -> Expr {
     match e {
         Var(i) => {
@@ -156,6 +160,7 @@ pub fn subst(n: i32, s: &Expr, e: &Expr) -> Expr {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn subst_top(s: &Expr, e: &Expr) -> Expr {
     /*| */
     shift(-1, &subst(0, &shift(1, s), e))
@@ -170,6 +175,7 @@ pub fn subst_top(s: &Expr, e: &Expr) -> Expr {
     /* |*/
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn pstep(expr: &Expr) -> Option<Expr> {
     match expr {
         Expr::Abs(t, e) => {
@@ -196,6 +202,7 @@ pub fn pstep(expr: &Expr) -> Option<Expr> {
     }
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", skip(step), ret))]
 pub fn multistep(f: usize, step: fn(&Expr) -> Option<Expr>, expr: &Expr) -> Option<Expr> {
     let mut current = expr.clone();
     for _ in 0..f {
@@ -208,6 +215,7 @@ pub fn multistep(f: usize, step: fn(&Expr) -> Option<Expr>, expr: &Expr) -> Opti
     Some(current)
 }
 
+#[cfg_attr(feature = "traced", tracing::instrument(level = "trace", ret))]
 pub fn is_nf(expr: &Expr) -> bool {
     match expr {
         Var(_) | Bool(_) => true,
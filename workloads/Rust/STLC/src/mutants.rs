@@ -0,0 +1,43 @@
+//! Runtime mutant selection. Only builds with the `schemata` feature carry
+//! the mutants; a plain build always runs the base code.
+
+#[cfg(feature = "schemata")]
+pub use crate::implementation::MUTANTS;
+
+#[cfg(not(feature = "schemata"))]
+pub const MUTANTS: &[&str] = &[];
+
+/// The generated implementation, with its marauder markers intact. Ground
+/// truth for a schemata build has to be read from here rather than from
+/// `src/implementation.rs`, whose line numbers differ.
+pub fn source() -> Option<&'static str> {
+    #[cfg(feature = "schemata")]
+    return Some(env!("FAULTLOC_SCHEMATA_SOURCE"));
+    #[cfg(not(feature = "schemata"))]
+    None
+}
+
+/// Switches on `names`. Without an explicit selection the mutants named in
+/// `FAULTLOC_MUTANTS` are used.
+pub fn select(names: &[String]) -> Result<(), String> {
+    if let Some(name) = names.iter().find(|name| !MUTANTS.contains(&name.as_str())) {
+        if MUTANTS.is_empty() {
+            return Err(format!(
+                "Cannot select mutant {}: built without the schemata feature",
+                name
+            ));
+        }
+        return Err(format!("Unknown mutant: {} (available: {})", name, MUTANTS.join(", ")));
+    }
+    #[cfg(feature = "schemata")]
+    faultloc_mutants::switch::select(names)?;
+    Ok(())
+}
+
+/// The mutants switched on for this run.
+pub fn selected() -> &'static [String] {
+    #[cfg(feature = "schemata")]
+    return faultloc_mutants::switch::selected();
+    #[cfg(not(feature = "schemata"))]
+    &[]
+}
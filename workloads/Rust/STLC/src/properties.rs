@@ -0,0 +1,50 @@
+//! The properties of the workload, shared by all of its binaries.
+
+use crate::{
+    parser,
+    spec::{
+        self,
+        ExprOpt,
+    },
+};
+
+/// Serialized tests are a list of expressions, each tested as is.
+fn parse(tests: &str) -> Result<Vec<ExprOpt>, String> {
+    Ok(parser::parse(tests)?.into_iter().map(|e| ExprOpt(Some(e))).collect())
+}
+
+faultloc_harness::properties! {
+    parse = parse;
+    SinglePreserve => prop_single_preserve(e: ExprOpt),
+    MultiPreserve => prop_multi_preserve(e: ExprOpt),
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        faultloc_harness::{
+            Input,
+            Property,
+            Runner,
+        },
+    };
+
+    struct Name;
+
+    impl Runner for Name {
+        type Output = &'static str;
+
+        fn run<A: Input>(self, property: Property<A>) -> &'static str {
+            property.name
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        for (name, _) in PROPERTIES {
+            assert_eq!(dispatch(name, Name), Some(*name));
+        }
+        assert_eq!(dispatch("single_preserve", Name), None);
+    }
+}
@@ -2,6 +2,10 @@ use crabcheck::quickcheck::Mutate;
 
 use {
     crabcheck::quickcheck::Arbitrary,
+    faultloc_harness::{
+        shrink::Shrink,
+        size::Size,
+    },
     rand::Rng,
 };
 
@@ -164,6 +168,78 @@ impl<R: Rng> Mutate<R> for ExprOpt {
     }
 }
 
+fn shrink_expr(e: &Expr) -> Vec<Expr> {
+    match e {
+        Expr::Var(i) => (0..*i).map(Expr::Var).collect(),
+        Expr::Bool(b) => b.shrink().into_iter().map(Expr::Bool).collect(),
+        Expr::Abs(t, body) => {
+            let mut candidates = vec![*body.clone()];
+            candidates
+                .extend(shrink_expr(body).into_iter().map(|b| Expr::Abs(t.clone(), Box::new(b))));
+            candidates
+        },
+        Expr::App(f, a) => {
+            let mut candidates = vec![*f.clone(), *a.clone()];
+            candidates.extend(shrink_expr(f).into_iter().map(|f| Expr::App(Box::new(f), a.clone())));
+            candidates.extend(shrink_expr(a).into_iter().map(|a| Expr::App(f.clone(), Box::new(a))));
+            candidates
+        },
+    }
+}
+
+impl Shrink for Expr {
+    fn shrink(&self) -> Vec<Self> {
+        // Structural candidates may lose a binder or change the type of a
+        // subterm; only the closed, well-typed ones are kept.
+        shrink_expr(self).into_iter().filter(|e| get_typ(&vec![], e).is_some()).collect()
+    }
+}
+
+impl Shrink for ExprOpt {
+    fn shrink(&self) -> Vec<Self> {
+        match &self.0 {
+            Some(e) => e.shrink().into_iter().map(|e| ExprOpt(Some(e))).collect(),
+            None => vec![],
+        }
+    }
+}
+
+impl Size for Expr {
+    fn size(&self) -> usize {
+        Expr::size(self)
+    }
+}
+
+impl Size for ExprOpt {
+    fn size(&self) -> usize {
+        self.0.as_ref().map_or(0, Expr::size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        rand::{
+            SeedableRng,
+            rngs::StdRng,
+        },
+    };
+
+    #[test]
+    fn test_shrink_keeps_well_typed() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in 1..50 {
+            let ExprOpt(Some(e)) = ExprOpt::generate(&mut rng, n) else { unreachable!() };
+            for candidate in e.shrink() {
+                assert!(get_typ(&vec![], &candidate).is_some(), "{} shrank to {}", e, candidate);
+                assert!(candidate.size() <= e.size());
+                assert_ne!(candidate, e);
+            }
+        }
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -0,0 +1,80 @@
+//! Invariant checkpoints.
+//!
+//! A property reports that an operation broke its data structure, not which of
+//! the internal functions behind the operation did. Workloads built with their
+//! `checked` feature call [`check`] on what those functions return, and the
+//! first value that fails its invariants is kept here until [`take`]n, so that
+//! `faultloc` can name the call that first produced an invalid state.
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Display},
+};
+
+/// The first checkpoint of an evaluation whose value was invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The function that returned the value.
+    pub function: &'static str,
+    /// Which checkpoint of the evaluation it was, counting from 1.
+    pub call: usize,
+    /// The value, as its `Debug` form.
+    pub state: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (checkpoint {}) returned {}", self.function, self.call, self.state)
+    }
+}
+
+thread_local! {
+    static CALLS: Cell<usize> = const { Cell::new(0) };
+    static FIRST: RefCell<Option<Violation>> = const { RefCell::new(None) };
+}
+
+/// Checks `state`, which `function` just returned. Once an evaluation has a
+/// violation, later checkpoints are counted but no longer validated.
+pub fn check<T: Debug>(function: &'static str, state: &T, valid: impl FnOnce(&T) -> bool) {
+    let call = CALLS.with(|calls| {
+        calls.set(calls.get() + 1);
+        calls.get()
+    });
+    FIRST.with(|first| {
+        let mut first = first.borrow_mut();
+        if first.is_none() && !valid(state) {
+            *first = Some(Violation { function, call, state: format!("{:?}", state) });
+        }
+    });
+}
+
+/// Starts a new evaluation.
+pub fn reset() {
+    CALLS.with(|calls| calls.set(0));
+    FIRST.with(|first| first.borrow_mut().take());
+}
+
+/// The first violation since the last [`reset`], if any, starting a new
+/// evaluation.
+pub fn take() -> Option<Violation> {
+    let first = FIRST.with(|first| first.borrow_mut().take());
+    reset();
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_violation() {
+        let positive = |x: &i32| *x > 0;
+        check("a", &1, positive);
+        check("b", &-2, positive);
+        check("c", &-3, positive);
+        assert_eq!(take(), Some(Violation { function: "b", call: 2, state: "-2".to_string() }));
+
+        check("a", &1, positive);
+        assert_eq!(take(), None);
+    }
+}
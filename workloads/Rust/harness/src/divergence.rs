@@ -0,0 +1,233 @@
+//! Model divergence reports.
+//!
+//! A model-based property compares what the implementation produced against
+//! an association-list model and fails with nothing but its input. A spec that
+//! compares through [`compare`] keeps the first mismatch of an evaluation as a
+//! [`Divergence`]: both lists, a minimal diff of their pairs and the first key
+//! they disagree on. `faultloc` reports it as text, and saves it as JSON to
+//! `coverage/divergence_<pid>-<group>.json` next to the snapshots of the
+//! failure, for `faultloc-analysis`.
+
+use std::{cell::RefCell, collections::BTreeSet, fmt::Display, path::PathBuf};
+
+use serde_json::json;
+
+use crate::snapshot::SNAPSHOT_DIR;
+
+/// A key/value pair of a model.
+pub type Pair = (i32, i32);
+
+/// One step of the diff from the expected list to the actual one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Edit {
+    /// A pair the model has and the implementation lost.
+    Missing(Pair),
+    /// A pair the implementation has and the model does not.
+    Unexpected(Pair),
+    /// A key both have, with a different value.
+    Changed { key: i32, expected: i32, actual: i32 },
+}
+
+impl Edit {
+    pub fn key(&self) -> i32 {
+        match self {
+            Edit::Missing((key, _)) | Edit::Unexpected((key, _)) | Edit::Changed { key, .. } => {
+                *key
+            },
+        }
+    }
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Edit::Missing(pair) => write!(f, "missing {:?}", pair),
+            Edit::Unexpected(pair) => write!(f, "unexpected {:?}", pair),
+            Edit::Changed { key, expected, actual } => {
+                write!(f, "changed {}: {} -> {}", key, expected, actual)
+            },
+        }
+    }
+}
+
+/// The expected and actual lists of a failed comparison and their diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub expected: Vec<Pair>,
+    pub actual: Vec<Pair>,
+    /// The fewest pairs to drop and add to turn `expected` into `actual`, in
+    /// list order, with a dropped and an added pair of the same key merged
+    /// into one change.
+    pub edits: Vec<Edit>,
+}
+
+impl Divergence {
+    pub fn new(expected: &[Pair], actual: &[Pair]) -> Divergence {
+        Divergence {
+            expected: expected.to_vec(),
+            actual: actual.to_vec(),
+            edits: diff(expected, actual),
+        }
+    }
+
+    /// The first key the lists disagree on.
+    pub fn first(&self) -> Option<i32> {
+        self.edits.first().map(Edit::key)
+    }
+
+    /// Every key the lists disagree on.
+    pub fn keys(&self) -> BTreeSet<i32> {
+        self.edits.iter().map(Edit::key).collect()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let edits = self
+            .edits
+            .iter()
+            .map(|edit| match edit {
+                Edit::Missing((key, value)) => {
+                    json!({ "op": "missing", "key": key, "value": value })
+                },
+                Edit::Unexpected((key, value)) => {
+                    json!({ "op": "unexpected", "key": key, "value": value })
+                },
+                Edit::Changed { key, expected, actual } => {
+                    json!({ "op": "changed", "key": key, "expected": expected, "actual": actual })
+                },
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "first": self.first(),
+            "keys": self.keys(),
+            "edits": edits,
+            "expected": self.expected,
+            "actual": self.actual,
+        })
+    }
+
+    /// Saves the divergence of recorded failure `group` as JSON.
+    pub fn save(&self, group: usize) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(SNAPSHOT_DIR)?;
+        let path = PathBuf::from(SNAPSHOT_DIR)
+            .join(format!("divergence_{}-{}.json", std::process::id(), group));
+        std::fs::write(&path, self.to_json().to_string())?;
+        Ok(path)
+    }
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.first() {
+            Some(first) => write!(f, "first at key {}: ", first)?,
+            None => write!(f, "no difference: ")?,
+        }
+        let edits = self.edits.iter().map(Edit::to_string).collect::<Vec<_>>();
+        write!(f, "{}", edits.join(", "))
+    }
+}
+
+/// A longest-common-subsequence diff of two lists of pairs.
+fn diff(expected: &[Pair], actual: &[Pair]) -> Vec<Edit> {
+    let (n, m) = (expected.len(), actual.len());
+    // `common[i][j]` is the longest common subsequence of `expected[i..]` and
+    // `actual[j..]`.
+    let mut common = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::<Edit>::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let edit = if j == m || (i < n && common[i + 1][j] >= common[i][j + 1]) {
+            i += 1;
+            Edit::Missing(expected[i - 1])
+        } else {
+            j += 1;
+            Edit::Unexpected(actual[j - 1])
+        };
+        let merged = match (edits.last().copied(), edit) {
+            (Some(Edit::Missing((key, expected))), Edit::Unexpected((k, actual)))
+            | (Some(Edit::Unexpected((k, actual))), Edit::Missing((key, expected)))
+                if key == k =>
+            {
+                Some(Edit::Changed { key, expected, actual })
+            },
+            _ => None,
+        };
+        match merged {
+            Some(merged) => *edits.last_mut().unwrap() = merged,
+            None => edits.push(edit),
+        }
+    }
+    edits
+}
+
+thread_local! {
+    static FIRST: RefCell<Option<Divergence>> = const { RefCell::new(None) };
+}
+
+/// Whether the implementation produced what the model expected. The first
+/// mismatch since the last [`take`] is kept.
+pub fn compare(expected: &[Pair], actual: &[Pair]) -> bool {
+    let equal = expected == actual;
+    if !equal {
+        FIRST.with(|first| {
+            first.borrow_mut().get_or_insert_with(|| Divergence::new(expected, actual));
+        });
+    }
+    equal
+}
+
+/// Starts a new evaluation.
+pub fn reset() {
+    take();
+}
+
+/// The first divergence since the last [`reset`] or call, if any.
+pub fn take() -> Option<Divergence> {
+    FIRST.with(|first| first.borrow_mut().take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let expected = [(1, 1), (3, 4), (5, 1), (8, 0)];
+        let actual = [(1, 1), (5, 2), (7, 0), (8, 0)];
+        let divergence = Divergence::new(&expected, &actual);
+        assert_eq!(
+            divergence.edits,
+            vec![
+                Edit::Missing((3, 4)),
+                Edit::Changed { key: 5, expected: 1, actual: 2 },
+                Edit::Unexpected((7, 0)),
+            ]
+        );
+        assert_eq!(divergence.first(), Some(3));
+        assert_eq!(
+            divergence.to_string(),
+            "first at key 3: missing (3, 4), changed 5: 1 -> 2, unexpected (7, 0)"
+        );
+        assert_eq!(divergence.to_json()["keys"], json!([3, 5, 7]));
+
+        assert!(compare(&expected, &expected));
+        assert_eq!(take(), None);
+        assert!(!compare(&expected, &actual));
+        assert!(!compare(&actual, &expected));
+        assert_eq!(take(), Some(divergence));
+    }
+}
@@ -0,0 +1,359 @@
+//! `faultloc`: runs a property under the profiling crabcheck, leaving one
+//! coverage snapshot per test. With `--record minimized` or `--record both`,
+//! a failure is shrunk and the minimized input leaves a snapshot of its own.
+//! With `--neighbors <n>`, each recorded failure is also mutated into up to
+//! `n` passing neighbors, leaving contrastive snapshots for
+//! `faultloc-analysis --contrastive`. In a `checked` build, each recorded
+//! failure is also reported with the first invalid state it went through.
+//! Failures of workloads whose specs keep a [`verdict::Verdict`] are reported
+//! with the invariants they broke, and failures of model-based properties
+//! with how the implementation diverged from the model, which is also saved
+//! next to the snapshots. With `--trace`, a `traced` build also leaves the
+//! call tree of each test in `traces/`.
+//! With `--outcomes`, a seeded run evaluates every input instead, failures
+//! included, and prints whether each passed (`P`), failed (`F`) or was
+//! discarded (`D`), for mutation-based fault localization.
+
+use {
+    crate::{
+        Input,
+        Property,
+        Runner,
+        Workload,
+        checkpoint::{
+            self,
+            Violation,
+        },
+        divergence::{
+            self,
+            Divergence,
+        },
+        neighbors::{
+            self,
+            Neighbors,
+        },
+        seed,
+        shrink::{
+            self,
+            Record,
+            Shrunk,
+        },
+        snapshot::{
+            self,
+            per_test,
+            per_test_if,
+        },
+        trace,
+        verdict::{
+            self,
+            Verdict,
+        },
+    },
+    crabcheck::quickcheck::RunResult,
+    std::{
+        cell::RefCell,
+        io::Write,
+        process::ExitCode,
+    },
+    tracing_subscriber::{
+        EnvFilter,
+        Layer,
+        layer::SubscriberExt,
+        util::SubscriberInitExt,
+    },
+};
+
+/// How many property evaluations shrinking a failure may take.
+const SHRINK_BUDGET: usize = 10_000;
+
+/// How many mutants of a failing input may be tried per passing neighbor
+/// wanted.
+const NEIGHBOR_ATTEMPTS: usize = 20;
+
+/// The size mutations run at. Small enough that a neighbor stays close to the
+/// failing input it was mutated from.
+const NEIGHBOR_SIZE: usize = 10;
+
+struct Profile<'a> {
+    options: &'a seed::Options,
+    record: Record,
+    /// How many passing neighbors to look for around each recorded failure.
+    neighbors: usize,
+}
+
+/// What the spec and the checkpoints of a workload said about a failure,
+/// beyond its coverage.
+struct Diagnosis {
+    /// The first invalid state, in a `checked` build.
+    invalid: Option<Violation>,
+    verdict: Option<Verdict>,
+    divergence: Option<Divergence>,
+}
+
+impl Diagnosis {
+    /// Starts a new evaluation.
+    fn reset() {
+        checkpoint::reset();
+        verdict::reset();
+        divergence::reset();
+    }
+
+    /// What the evaluation since the last [`Diagnosis::reset`] left behind.
+    fn take() -> Diagnosis {
+        Diagnosis {
+            invalid: checkpoint::take(),
+            verdict: verdict::take(),
+            divergence: divergence::take(),
+        }
+    }
+}
+
+struct Profiled {
+    result: RunResult,
+    /// The minimized failure, when the run failed and was asked to shrink.
+    shrunk: Option<Shrunk<String>>,
+    /// The neighbor search around each recorded failure, in group order.
+    neighbors: Vec<Neighbors>,
+    /// The diagnosis of each recorded failure, in group order.
+    diagnoses: Vec<Diagnosis>,
+}
+
+impl Runner for Profile<'_> {
+    type Output = Profiled;
+
+    fn run<A: Input>(self, property: Property<A>) -> Profiled {
+        // crabcheck only reports the failing input as a string, so the
+        // property keeps the input itself for shrinking and mutation.
+        let failure = RefCell::new(None);
+        let observed = |input: A| {
+            Diagnosis::reset();
+            let result = (property.function)(input.clone());
+            if result == Some(false) {
+                *failure.borrow_mut() = Some((input, Diagnosis::take()));
+            }
+            result
+        };
+        let record = self.record;
+        let keep = move |result| record != Record::Minimized || result != Some(false);
+        let observed = trace::per_test_if(observed, keep);
+        let result = seed::crabcheck_profiling(self.options, per_test_if(observed, keep));
+
+        let Some((input, diagnosis)) = failure.into_inner() else {
+            return Profiled { result, shrunk: None, neighbors: vec![], diagnoses: vec![] };
+        };
+        let mut anchors = vec![];
+        let mut diagnoses = vec![];
+        if record != Record::Minimized {
+            anchors.push(input.clone());
+            diagnoses.push(diagnosis);
+        }
+        let mut shrunk = None;
+        if record != Record::Original {
+            let minimized = shrink::minimize(input, property.function, SHRINK_BUDGET);
+            Diagnosis::reset();
+            per_test(trace::per_test_if(property.function, |_| true))(minimized.input.clone());
+            diagnoses.push(Diagnosis::take());
+            shrunk = Some(Shrunk {
+                input: format!("{:?}", minimized.input),
+                steps: minimized.steps,
+                evaluations: minimized.evaluations,
+            });
+            anchors.push(minimized.input);
+        }
+
+        let mut neighbors = vec![];
+        if self.neighbors > 0 {
+            // A stream of the seed that no test index reaches, so that seeded
+            // runs find the same neighbors every time.
+            let seed = self.options.seed().unwrap_or_else(rand::random);
+            let mut rng = seed::rng(seed, usize::MAX);
+            for (group, anchor) in anchors.iter().enumerate() {
+                snapshot::contrast(property.function, group, 0, anchor.clone(), |_| true);
+                neighbors.push(neighbors::search(
+                    anchor,
+                    &mut rng,
+                    NEIGHBOR_SIZE,
+                    self.neighbors,
+                    self.neighbors * NEIGHBOR_ATTEMPTS,
+                    |member, neighbor| {
+                        let pass = |result| result == Some(true);
+                        snapshot::contrast(property.function, group, member, neighbor, pass)
+                    },
+                ));
+            }
+        }
+        Profiled { result, shrunk, neighbors, diagnoses }
+    }
+}
+
+/// Prints the outcome of every input of a seeded run as it comes, so that a
+/// run killed halfway still tells how far it got.
+struct Outcomes {
+    seed: u64,
+    tests: usize,
+}
+
+impl Runner for Outcomes {
+    type Output = ();
+
+    fn run<A: Input>(self, property: Property<A>) {
+        // A panic is a failing outcome, not something to report.
+        std::panic::set_hook(Box::new(|_| {}));
+        let mut stdout = std::io::stdout();
+        let generate = seed::crabcheck_input::<A>;
+        print!("Outcomes: ");
+        seed::outcomes(self.seed, self.tests, generate, property.function, |outcome| {
+            let outcome = match outcome {
+                Some(true) => 'P',
+                Some(false) => 'F',
+                None => 'D',
+            };
+            print!("{}", outcome);
+            let _ = stdout.flush();
+        });
+        println!();
+    }
+}
+
+pub fn main<W: Workload>() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    if crate::listing(&args) {
+        crate::list::<W>();
+        return ExitCode::SUCCESS;
+    }
+    if args.len() < 3 {
+        eprintln!(
+            "Usage: {} <tool> <property> [--mutant <name>]... [--seed <n> | --replay <n> --index <i>] [--tests <n>] [--record <original|minimized|both>] [--neighbors <n>] [--trace] [--outcomes]",
+            args[0]
+        );
+        eprintln!("Available tools: crabcheck");
+        eprintln!("For available properties, run {} --list-properties", args[0]);
+        return ExitCode::FAILURE;
+    }
+    let tool = args[1].as_str();
+    let property = args[2].as_str();
+
+    let (options, rest) = match seed::Options::parse(&args[3..], W::FAULTLOC_TESTS) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let mut selection = vec![];
+    let mut record = Record::Original;
+    let mut neighbors = 0;
+    let mut traced = false;
+    let mut outcomes = false;
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| format!("Missing value for {}", arg));
+        let parsed = match arg.as_str() {
+            "--trace" => {
+                traced = true;
+                Ok(())
+            },
+            "--outcomes" => {
+                outcomes = true;
+                Ok(())
+            },
+            "--mutant" => value().map(|value| selection.push(value.clone())),
+            "--record" => {
+                value().and_then(|value| Record::parse(value)).map(|value| record = value)
+            },
+            "--neighbors" => value().and_then(|value| {
+                value
+                    .parse::<usize>()
+                    .map(|value| neighbors = value)
+                    .map_err(|_| format!("Invalid value for --neighbors: {}", value))
+            }),
+            _ => Err(format!("Unknown argument: {}", arg)),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    // Traces record `trace` level spans, whatever `RUST_LOG` lets through.
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(true)
+                .with_filter(EnvFilter::from_default_env()),
+        )
+        .with(traced.then(trace::Recorder::enable))
+        .init();
+
+    if !selection.is_empty()
+        && let Err(e) = W::select(&selection)
+    {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+
+    if outcomes {
+        let seed::Mode::Seeded(seed) = options.mode else {
+            eprintln!("--outcomes needs --seed");
+            return ExitCode::FAILURE;
+        };
+        W::dispatch(property, Outcomes { seed, tests: options.tests })
+            .unwrap_or_else(|| panic!("Unknown property: {}", property));
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match tool {
+        "crabcheck" => W::dispatch(property, Profile { options: &options, record, neighbors }),
+        _ => None,
+    }
+    .unwrap_or_else(|| panic!("Unknown tool or property: {} {}", tool, property));
+
+    println!("Result: {:?}", result.result);
+    if let Some(shrunk) = result.shrunk {
+        println!(
+            "Minimized: {} ({} steps, {} evaluations)",
+            shrunk.input, shrunk.steps, shrunk.evaluations
+        );
+    }
+    for (group, neighbors) in result.neighbors.iter().enumerate() {
+        println!(
+            "Neighbors of failure {}: {} passing in {} attempts",
+            group, neighbors.found, neighbors.attempts
+        );
+    }
+    for (group, diagnosis) in result.diagnoses.iter().enumerate() {
+        match &diagnosis.invalid {
+            Some(violation) => println!("First invalid state of failure {}: {}", group, violation),
+            None if W::CHECKED => println!("First invalid state of failure {}: none", group),
+            None => {},
+        }
+        if let Some(verdict) = &diagnosis.verdict {
+            println!("Verdict of failure {}: {}", group, verdict);
+        }
+        if let Some(divergence) = &diagnosis.divergence {
+            println!("Divergence of failure {}: {}", group, divergence);
+            println!("  expected: {:?}", divergence.expected);
+            println!("  actual:   {:?}", divergence.actual);
+            if let Err(e) = divergence.save(group) {
+                eprintln!("Failed to save the divergence of failure {}: {}", group, e);
+            }
+        }
+    }
+    if let Some(seed) = options.seed() {
+        println!("Seed: {}", seed);
+    }
+    if !W::selected().is_empty() {
+        println!("Mutants: {}", W::selected().join(", "));
+        if let Some(source) = W::source() {
+            println!("Schemata source: {}", source);
+        }
+    }
+    if snapshot::is_instrumented() {
+        println!("Coverage snapshots: {}", snapshot::count());
+    }
+    if trace::is_enabled() {
+        println!("Traces: {}", trace::count());
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,75 @@
+//! The binaries every workload ships, written once against [`Workload`].
+//!
+//! A workload crate implements [`Workload`] and keeps one-line binaries that
+//! hand over to [`run`], [`sample`], [`serialized`] and [`faultloc`].
+
+#![feature(linkage)]
+
+pub mod checkpoint;
+pub mod divergence;
+pub mod faultloc;
+pub mod neighbors;
+pub mod property;
+pub mod run;
+pub mod sample;
+pub mod seed;
+pub mod serialized;
+pub mod shrink;
+pub mod size;
+pub mod snapshot;
+pub mod trace;
+pub mod verdict;
+
+pub use property::{
+    Input,
+    Property,
+    Runner,
+};
+
+/// A benchmark workload: its properties, with their input types, generators,
+/// serialized form and printer, and the mutants it was built with.
+pub trait Workload {
+    /// The crate name, as the binaries are named.
+    const NAME: &'static str;
+
+    /// How many tests `run` tries before giving up on finding a failure.
+    const TESTS: u64;
+
+    /// How many tests a seeded `faultloc` run tries by default.
+    const FAULTLOC_TESTS: usize;
+
+    /// Every property with its signature, as [`properties!`] lists them.
+    const PROPERTIES: &'static [(&'static str, &'static str)];
+
+    /// Whether the build calls [`checkpoint::check`] on what its internal
+    /// functions return.
+    const CHECKED: bool = false;
+
+    /// Runs the property called `name`, if there is one.
+    fn dispatch<R: Runner>(name: &str, runner: R) -> Option<R::Output>;
+
+    /// The mutants compiled in by a schemata build.
+    fn mutants() -> &'static [&'static str];
+
+    /// Switches on the mutants in `names`.
+    fn select(names: &[String]) -> Result<(), String>;
+
+    /// The mutants switched on for this run.
+    fn selected() -> &'static [String];
+
+    /// The generated implementation of a schemata build.
+    fn source() -> Option<&'static str>;
+}
+
+/// Prints every property of `W` with its signature, for `--list-properties`.
+pub fn list<W: Workload>() {
+    let width = W::PROPERTIES.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, signature) in W::PROPERTIES {
+        println!("{:width$}  {}", name, signature, width = width);
+    }
+}
+
+/// Whether the binary was asked for `--list-properties`.
+fn listing(args: &[String]) -> bool {
+    args.get(1).map(String::as_str) == Some("--list-properties")
+}
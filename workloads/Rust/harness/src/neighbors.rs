@@ -0,0 +1,84 @@
+//! Contrastive neighbor sampling.
+//!
+//! A failing input and random passing tests differ in many ways that have
+//! nothing to do with the fault. Mutating the failing input instead gives
+//! passing inputs that are close to it, so that the coverage that tells them
+//! apart is more likely to be the fault's. The `Mutate` impls of the bespoke
+//! crabcheck strategies keep the generator's invariants, so every neighbor is
+//! a valid input.
+
+use {
+    crabcheck::quickcheck::Mutate,
+    rand::Rng,
+};
+
+/// How a search for the passing neighbors of a failing input went.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Neighbors {
+    pub found: usize,
+    pub attempts: usize,
+}
+
+/// Mutates `anchor` until `wanted` of its mutants pass or `budget` mutants
+/// have been tried. `evaluate` runs the property on a mutant, given the
+/// number it gets if it passes: 1 for the first passing neighbor, and so on.
+pub fn search<A, R, E>(
+    anchor: &A,
+    rng: &mut R,
+    size: usize,
+    wanted: usize,
+    budget: usize,
+    mut evaluate: E,
+) -> Neighbors
+where
+    A: Mutate<R>,
+    R: Rng,
+    E: FnMut(usize, A) -> Option<bool>,
+{
+    let mut neighbors = Neighbors { found: 0, attempts: 0 };
+    while neighbors.found < wanted && neighbors.attempts < budget {
+        neighbors.attempts += 1;
+        if evaluate(neighbors.found + 1, anchor.mutate(rng, size)) == Some(true) {
+            neighbors.found += 1;
+        }
+    }
+    neighbors
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        rand::{
+            SeedableRng,
+            rngs::StdRng,
+        },
+    };
+
+    struct Near(i32);
+
+    impl<R: Rng> Mutate<R> for Near {
+        fn mutate(&self, rng: &mut R, size: usize) -> Self {
+            Near(self.0 + rng.random_range(-(size as i32)..=size as i32))
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut members = vec![];
+        let neighbors = search(&Near(0), &mut rng, 3, 5, 1000, |member, Near(x)| {
+            let result = Some(x != 0);
+            if result == Some(true) {
+                members.push(member);
+            }
+            result
+        });
+        assert_eq!(neighbors.found, 5);
+        assert!(neighbors.attempts >= 5);
+        assert_eq!(members, vec![1, 2, 3, 4, 5]);
+
+        let neighbors = search(&Near(0), &mut rng, 0, 5, 10, |_, Near(x)| Some(x != 0));
+        assert_eq!(neighbors, Neighbors { found: 0, attempts: 10 });
+    }
+}
@@ -0,0 +1,118 @@
+//! Properties as the harness sees them, whatever their input type.
+//!
+//! A workload lists its properties with [`properties!`](crate::properties),
+//! which maps each property name to its `spec::prop_*` function and the
+//! arguments it is tested on. A property of several arguments takes them as
+//! one tuple, so that every strategy generates the whole input at once; a
+//! property of one argument takes it as is.
+
+use {
+    crate::{
+        shrink::Shrink,
+        size::Size,
+    },
+    rand::rngs::{
+        StdRng,
+        ThreadRng,
+    },
+    std::fmt::Debug,
+};
+
+/// What every mode needs from a property input: generating and mutating it
+/// under crabcheck and quickcheck, shrinking or mutating it once it fails, and
+/// telling its size to the snapshot it is recorded in.
+pub trait Input:
+    crabcheck::quickcheck::Arbitrary<ThreadRng>
+    + crabcheck::quickcheck::Arbitrary<StdRng>
+    + crabcheck::quickcheck::Mutate<ThreadRng>
+    + crabcheck::quickcheck::Mutate<StdRng>
+    + quickcheck::Arbitrary
+    + Shrink
+    + Size
+    + Clone
+    + Debug
+{
+}
+
+impl<A> Input for A where
+    A: crabcheck::quickcheck::Arbitrary<ThreadRng>
+        + crabcheck::quickcheck::Arbitrary<StdRng>
+        + crabcheck::quickcheck::Mutate<ThreadRng>
+        + crabcheck::quickcheck::Mutate<StdRng>
+        + quickcheck::Arbitrary
+        + Shrink
+        + Size
+        + Clone
+        + Debug
+{
+}
+
+pub struct Property<A> {
+    pub name: &'static str,
+    pub function: fn(A) -> Option<bool>,
+    /// Reads the list of inputs of a serialized test file.
+    pub parse: fn(&str) -> Result<Vec<A>, String>,
+    /// Renders an input to report a failure: the argument itself, or the
+    /// arguments as an s-expression list.
+    pub show: fn(&A) -> String,
+}
+
+/// Runs a property, whichever its input type.
+pub trait Runner {
+    type Output;
+
+    fn run<A: Input>(self, property: Property<A>) -> Self::Output;
+}
+
+/// Defines `PROPERTIES` and `dispatch` for a workload, from its `spec`
+/// module and a `parse` function for serialized tests that is generic over,
+/// or fixed to, the input types.
+///
+/// ```ignore
+/// faultloc_harness::properties! {
+///     parse = parse;
+///     InsertValid => prop_insert_valid(t: Tree, k: i32, v: i32),
+///     UnionUnionIdempotent => prop_union_union_idempotent(t: Tree),
+/// }
+/// ```
+#[macro_export]
+macro_rules! properties {
+    (@input $arg:ident: $ty:ty) => { $ty };
+    (@input $($arg:ident: $ty:ty),+) => { ($($ty),+) };
+    (@pattern $arg:ident) => { $arg };
+    (@pattern $($arg:ident),+) => { ($($arg),+) };
+    (@show $arg:ident) => { $arg.to_string() };
+    (@show $($arg:ident),+) => { format!("({})", [$($arg.to_string()),+].join(" ")) };
+
+    (
+        parse = $parse:path;
+        $($name:ident => $function:ident ($($arg:ident: $ty:ty),+ $(,)?)),* $(,)?
+    ) => {
+        /// Every property with its signature, in the order they are listed.
+        pub const PROPERTIES: &[(&str, &str)] = &[
+            $((
+                stringify!($name),
+                concat!("spec::", stringify!($function), "(", stringify!($($ty),+), ")"),
+            ),)*
+        ];
+
+        /// Runs the property called `name`, if there is one.
+        pub fn dispatch<R: $crate::Runner>(name: &str, runner: R) -> Option<R::Output> {
+            match name {
+                $(stringify!($name) => Some(runner.run($crate::Property {
+                    name: stringify!($name),
+                    function: |input: $crate::properties!(@input $($arg: $ty),+)| {
+                        let $crate::properties!(@pattern $($arg),+) = input;
+                        spec::$function($($arg),+)
+                    },
+                    parse: $parse,
+                    show: |input: &$crate::properties!(@input $($arg: $ty),+)| {
+                        let $crate::properties!(@pattern $($arg),+) = input;
+                        $crate::properties!(@show $($arg),+)
+                    },
+                })),)*
+                _ => None,
+            }
+        }
+    };
+}
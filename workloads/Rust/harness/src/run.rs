@@ -0,0 +1,144 @@
+//! `run`: checks a property with quickcheck or crabcheck until it fails or
+//! runs out of tests, reporting in quickcheck's terms either way.
+
+use {
+    crate::{
+        Input,
+        Property,
+        Runner,
+        Workload,
+        seed,
+    },
+    std::time::Duration,
+    tracing_subscriber::EnvFilter,
+};
+
+trait QuickCheckResultExt {
+    fn to_qc_result(self) -> quickcheck::QuickCheckResult;
+}
+
+impl QuickCheckResultExt for crabcheck::quickcheck::RunResult {
+    fn to_qc_result(self) -> quickcheck::QuickCheckResult {
+        match self.status {
+            crabcheck::quickcheck::ResultStatus::Finished => {
+                quickcheck::QuickCheckResult {
+                    n_tests_passed: self.passed,
+                    n_tests_discarded: self.discarded,
+                    status: quickcheck::ResultStatus::Finished,
+                    total_time: Duration::default(),
+                    generation_time: Duration::default(),
+                    shrinking_time: Duration::default(),
+                    execution_time: Duration::default(),
+                }
+            },
+            crabcheck::quickcheck::ResultStatus::Failed { arguments } => {
+                quickcheck::QuickCheckResult {
+                    n_tests_passed: self.passed,
+                    n_tests_discarded: self.discarded,
+                    status: quickcheck::ResultStatus::Failed { arguments },
+                    total_time: Duration::default(),
+                    generation_time: Duration::default(),
+                    shrinking_time: Duration::default(),
+                    execution_time: Duration::default(),
+                }
+            },
+            crabcheck::quickcheck::ResultStatus::GaveUp => {
+                quickcheck::QuickCheckResult {
+                    n_tests_passed: self.passed,
+                    n_tests_discarded: self.discarded,
+                    status: quickcheck::ResultStatus::GaveUp,
+                    total_time: Duration::default(),
+                    generation_time: Duration::default(),
+                    shrinking_time: Duration::default(),
+                    execution_time: Duration::default(),
+                }
+            },
+            crabcheck::quickcheck::ResultStatus::TimedOut => {
+                quickcheck::QuickCheckResult {
+                    n_tests_passed: self.passed,
+                    n_tests_discarded: self.discarded,
+                    status: quickcheck::ResultStatus::TimedOut,
+                    total_time: Duration::default(),
+                    generation_time: Duration::default(),
+                    shrinking_time: Duration::default(),
+                    execution_time: Duration::default(),
+                }
+            },
+            crabcheck::quickcheck::ResultStatus::Aborted { error } => {
+                quickcheck::QuickCheckResult {
+                    n_tests_passed: self.passed,
+                    n_tests_discarded: self.discarded,
+                    status: quickcheck::ResultStatus::Aborted { err: Some(error) },
+                    total_time: Duration::default(),
+                    generation_time: Duration::default(),
+                    shrinking_time: Duration::default(),
+                    execution_time: Duration::default(),
+                }
+            },
+        }
+    }
+}
+
+struct Test<'a> {
+    tool: &'a str,
+    qc: &'a mut quickcheck::QuickCheck,
+    options: &'a seed::Options,
+}
+
+impl Runner for Test<'_> {
+    type Output = Option<quickcheck::QuickCheckResult>;
+
+    fn run<A: Input>(self, property: Property<A>) -> Self::Output {
+        match self.tool {
+            "quickcheck" => Some(seed::quicktest(self.qc, self.options, property.function)),
+            "crabcheck" => Some(seed::crabcheck(self.options, property.function).to_qc_result()),
+            _ => None,
+        }
+    }
+}
+
+pub fn main<W: Workload>() {
+    let args = std::env::args().collect::<Vec<_>>();
+    tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env()).with_ansi(true).init();
+    if crate::listing(&args) {
+        crate::list::<W>();
+        return;
+    }
+    if args.len() < 3 {
+        eprintln!(
+            "Usage: {} <tool> <property> [--seed <n> | --replay <n> --index <i>] [--tests <n>]",
+            args[0]
+        );
+        eprintln!("Available tools: quickcheck, crabcheck");
+        eprintln!("For available properties, run {} --list-properties", args[0]);
+        return;
+    }
+    let tool = args[1].as_str();
+    let property = args[2].as_str();
+
+    let num_tests = W::TESTS;
+    let options = match seed::Options::parse(&args[3..], num_tests as usize) {
+        Ok((options, rest)) if rest.is_empty() => options,
+        Ok((_, rest)) => {
+            eprintln!("Unknown argument: {}", rest[0]);
+            return;
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+    let mut qc = quickcheck::QuickCheck::new()
+        .tests(num_tests)
+        .max_tests(num_tests * 2)
+        .max_time(Duration::from_secs(60 * 60));
+
+    let result = W::dispatch(property, Test { tool, qc: &mut qc, options: &options })
+        .flatten()
+        .unwrap_or_else(|| panic!("Unknown tool or property: {} {}", tool, property));
+
+    if let Some(seed) = options.seed() {
+        eprintln!("Seed: {}", seed);
+    }
+    result.print_status();
+}
@@ -0,0 +1,94 @@
+//! `sample`: times quickcheck generating inputs for a property, printing them
+//! as a JSON array of `{time, value}` objects.
+
+use {
+    crate::{
+        Input,
+        Property,
+        Runner,
+        Workload,
+        seed,
+    },
+    std::time::Duration,
+};
+
+struct Sample<'a> {
+    qc: &'a mut quickcheck::QuickCheck,
+    options: &'a seed::Options,
+}
+
+impl Runner for Sample<'_> {
+    type Output = Vec<(Duration, String)>;
+
+    fn run<A: Input>(self, property: Property<A>) -> Self::Output {
+        seed::quicksample(self.qc, self.options, property.function)
+    }
+}
+
+pub fn main<W: Workload>() {
+    let args = std::env::args().collect::<Vec<_>>();
+    if crate::listing(&args) {
+        crate::list::<W>();
+        return;
+    }
+    if args.len() < 4 {
+        eprintln!(
+            "Usage: {} <tool> <property> <tests> [--seed <n> | --replay <n> --index <i>]",
+            args[0]
+        );
+        eprintln!("Available tools: quickcheck");
+        eprintln!("For available properties, run {} --list-properties", args[0]);
+        return;
+    }
+    let tool = args[1].as_str();
+    let property = args[2].as_str();
+    let tests = args[3].as_str();
+
+    let num_tests = tests
+        .parse::<u64>()
+        .expect(format!("Failed to parse number of tests: '{}'", tests).as_str());
+    let options = match seed::Options::parse(&args[4..], num_tests as usize) {
+        Ok((options, rest)) if rest.is_empty() => options,
+        Ok((_, rest)) => {
+            eprintln!("Unknown argument: {}", rest[0]);
+            return;
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+    let mut qc = quickcheck::QuickCheck::new()
+        .tests(num_tests)
+        .max_tests(num_tests * 2)
+        .max_time(Duration::from_secs(1));
+
+    let result = match tool {
+        "quickcheck" => W::dispatch(property, Sample { qc: &mut qc, options: &options }),
+        _ => None,
+    }
+    .unwrap_or_else(|| panic!("Unknown tool or property: {} {}", tool, property));
+
+    // Keep stdout to the JSON array.
+    if let Some(seed) = options.seed() {
+        eprintln!("Seed: {}", seed);
+    }
+
+    let mut results = Vec::<serde_json::Value>::new();
+
+    for (duration, element) in result {
+        let mut object = serde_json::Map::new();
+        object.insert(
+            "time".to_string(),
+            serde_json::Value::String(format!("{}ns", duration.as_nanos())),
+        );
+        object.insert("value".to_string(), serde_json::Value::String(element.to_string()));
+        results.push(serde_json::Value::Object(object));
+    }
+
+    let results = serde_json::Value::Array(results);
+
+    let output = serde_json::to_string(&results).expect("Failed to serialize results to JSON");
+
+    println!("{}", output);
+}
@@ -0,0 +1,369 @@
+//! Seeded, replayable test-case generation.
+//!
+//! crabcheck's `profiling::quickcheck` draws its inputs from the thread RNG,
+//! and the quickcheck fork from its own, neither of which takes a seed, so
+//! seeded runs drive the generators here instead. Test `index` of a
+//! run with `seed` draws its input from its own [`StdRng`], derived from both
+//! numbers and sized by [`size`]; any single input can then be regenerated with
+//! `--replay <seed> --index <index>` without replaying the tests before it.
+//! Inputs are reproducible for a given version of `rand` and the generators.
+//!
+//! A seeded run is therefore not a run of the testing library: it tries
+//! `--tests` inputs at the sizes of [`size`] rather than the library's budget
+//! and sizing, and `faultloc-runner` stores its trials under
+//! `<strategy>-seeded`.
+//!
+//! Under [`crate::snapshot::per_test`], snapshot `index` of a seeded run is the
+//! evaluation of test `index`.
+
+use {
+    crabcheck::quickcheck::{
+        Arbitrary,
+        Mutate,
+        ResultStatus,
+        RunResult,
+    },
+    rand::{
+        Rng,
+        SeedableRng,
+        rngs::{
+            StdRng,
+            ThreadRng,
+        },
+    },
+    std::{
+        fmt::Debug,
+        panic::{
+            self,
+            AssertUnwindSafe,
+        },
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Leave generation to the testing library, as before seeds existed.
+    Unseeded,
+    Seeded(u64),
+    /// Regenerate and evaluate the single input `index` of a seeded run.
+    Replay { seed: u64, index: usize },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    /// How many inputs a seeded run tries.
+    pub tests: usize,
+}
+
+impl Options {
+    /// Takes `--seed`, `--replay`, `--index` and `--tests` out of `args` and
+    /// returns the arguments left over.
+    pub fn parse(args: &[String], tests: usize) -> Result<(Options, Vec<String>), String> {
+        let mut seed = None;
+        let mut replay = None;
+        let mut index = None;
+        let mut tests = tests;
+        let mut rest = vec![];
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                let value = args.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+                value.parse::<u64>().map_err(|_| format!("Invalid value for {}: {}", arg, value))
+            };
+            match arg.as_str() {
+                "--seed" => seed = Some(value()?),
+                "--replay" => replay = Some(value()?),
+                "--index" => index = Some(value()? as usize),
+                "--tests" => tests = value()? as usize,
+                _ => rest.push(arg.clone()),
+            }
+        }
+
+        let mode = match (seed, replay, index) {
+            (None, None, None) => Mode::Unseeded,
+            (Some(seed), None, None) => Mode::Seeded(seed),
+            (None, Some(seed), Some(index)) => Mode::Replay { seed, index },
+            (None, Some(_), None) => return Err("--replay needs --index".to_string()),
+            (_, None, Some(_)) => return Err("--index only applies to --replay".to_string()),
+            (Some(_), Some(_), _) => return Err("--seed and --replay are exclusive".to_string()),
+        };
+        Ok((Options { mode, tests }, rest))
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        match self.mode {
+            Mode::Unseeded => None,
+            Mode::Seeded(seed) | Mode::Replay { seed, .. } => Some(seed),
+        }
+    }
+}
+
+// SplitMix64, to spread consecutive seeds and indices over the whole range.
+fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_4764_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn rng(seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(mix(seed ^ mix(index as u64)))
+}
+
+/// The size test `index` is generated at, cycling from 1 to 100.
+pub fn size(index: usize) -> usize {
+    index % 100 + 1
+}
+
+/// Generates an input through the crabcheck strategies.
+pub fn crabcheck_input<A: Arbitrary<StdRng>>(rng: &mut StdRng, size: usize) -> A {
+    A::generate(rng, size)
+}
+
+/// Generates an input through the quickcheck strategies, from a `Gen` seeded
+/// off `rng`.
+pub fn quickcheck_input<A: quickcheck::Arbitrary>(rng: &mut StdRng, size: usize) -> A {
+    let mut g = quickcheck::Gen::from_size_and_seed(size, rng.random());
+    A::arbitrary(&mut g)
+}
+
+pub fn replay<A, G>(seed: u64, index: usize, generate: G) -> A
+where
+    G: Fn(&mut StdRng, usize) -> A,
+{
+    generate(&mut rng(seed, index), size(index))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub index: usize,
+    pub input: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub seed: u64,
+    pub passed: u64,
+    pub discarded: u64,
+    pub failure: Option<Failure>,
+}
+
+impl Report {
+    pub fn to_run_result(&self) -> RunResult {
+        RunResult {
+            passed: self.passed,
+            discarded: self.discarded,
+            status: match &self.failure {
+                Some(failure) => ResultStatus::Failed { arguments: vec![failure.input.clone()] },
+                None => ResultStatus::Finished,
+            },
+        }
+    }
+
+    pub fn to_qc_result(&self) -> quickcheck::QuickCheckResult {
+        quickcheck::QuickCheckResult {
+            n_tests_passed: self.passed,
+            n_tests_discarded: self.discarded,
+            status: match &self.failure {
+                Some(failure) => {
+                    quickcheck::ResultStatus::Failed { arguments: vec![failure.input.clone()] }
+                },
+                None => quickcheck::ResultStatus::Finished,
+            },
+            total_time: Duration::default(),
+            generation_time: Duration::default(),
+            shrinking_time: Duration::default(),
+            execution_time: Duration::default(),
+        }
+    }
+}
+
+/// Evaluates `property` on inputs `0..tests` of `seed`, stopping at the first
+/// failure.
+pub fn check<A, G, F>(seed: u64, tests: usize, generate: G, property: F) -> Report
+where
+    A: Clone + Debug,
+    G: Fn(&mut StdRng, usize) -> A,
+    F: Fn(A) -> Option<bool>,
+{
+    let mut report = Report { seed, passed: 0, discarded: 0, failure: None };
+    for index in 0..tests {
+        let input = replay(seed, index, &generate);
+        match property(input.clone()) {
+            Some(true) => report.passed += 1,
+            None => report.discarded += 1,
+            Some(false) => {
+                println!(
+                    "Test {} of seed {} failed, replay it with --replay {} --index {}",
+                    index, seed, seed, index
+                );
+                report.failure = Some(Failure { index, input: format!("{:?}", input) });
+                break;
+            },
+        }
+    }
+    report
+}
+
+/// Evaluates `property` on every input `0..tests` of `seed`, failures
+/// included, handing each outcome to `observe` as soon as it is known. A
+/// panicking evaluation counts as a failure.
+pub fn outcomes<A, G, F, O>(seed: u64, tests: usize, generate: G, property: F, mut observe: O)
+where
+    G: Fn(&mut StdRng, usize) -> A,
+    F: Fn(A) -> Option<bool>,
+    O: FnMut(Option<bool>),
+{
+    for index in 0..tests {
+        let input = replay(seed, index, &generate);
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| property(input)));
+        observe(outcome.unwrap_or(Some(false)));
+    }
+}
+
+fn replay_once<A, G, F>(seed: u64, index: usize, generate: G, property: F) -> Report
+where
+    A: Clone + Debug,
+    G: Fn(&mut StdRng, usize) -> A,
+    F: Fn(A) -> Option<bool>,
+{
+    let input = replay(seed, index, generate);
+    println!("Input {} of seed {}: {:?}", index, seed, input);
+    let mut report = Report { seed, passed: 0, discarded: 0, failure: None };
+    match property(input.clone()) {
+        Some(true) => report.passed = 1,
+        None => report.discarded = 1,
+        Some(false) => report.failure = Some(Failure { index, input: format!("{:?}", input) }),
+    }
+    report
+}
+
+fn run<A, G, F>(options: &Options, generate: G, property: F) -> Option<Report>
+where
+    A: Clone + Debug,
+    G: Fn(&mut StdRng, usize) -> A,
+    F: Fn(A) -> Option<bool>,
+{
+    match options.mode {
+        Mode::Unseeded => None,
+        Mode::Seeded(seed) => Some(check(seed, options.tests, generate, property)),
+        Mode::Replay { seed, index } => Some(replay_once(seed, index, generate, property)),
+    }
+}
+
+/// `crabcheck::quickcheck::quickcheck`, seeded when `options` asks for it.
+pub fn crabcheck<A, F>(options: &Options, property: F) -> RunResult
+where
+    A: Arbitrary<ThreadRng> + Arbitrary<StdRng> + Clone + Debug,
+    F: Fn(A) -> Option<bool>,
+{
+    match run(options, crabcheck_input::<A>, &property) {
+        Some(report) => report.to_run_result(),
+        None => crabcheck::quickcheck::quickcheck(property),
+    }
+}
+
+/// `crabcheck::profiling::quickcheck`, seeded when `options` asks for it.
+pub fn crabcheck_profiling<A, F>(options: &Options, property: F) -> RunResult
+where
+    A: Arbitrary<ThreadRng> + Arbitrary<StdRng> + Mutate<ThreadRng> + Clone + Debug,
+    F: Fn(A) -> Option<bool>,
+{
+    match run(options, crabcheck_input::<A>, &property) {
+        Some(report) => report.to_run_result(),
+        None => crabcheck::profiling::quickcheck(property),
+    }
+}
+
+/// `QuickCheck::quicktest`, seeded when `options` asks for it.
+pub fn quicktest<A>(
+    qc: &mut quickcheck::QuickCheck,
+    options: &Options,
+    property: fn(A) -> Option<bool>,
+) -> quickcheck::QuickCheckResult
+where
+    A: quickcheck::Arbitrary + Debug,
+    fn(A) -> Option<bool>: quickcheck::Testable,
+{
+    match run(options, quickcheck_input::<A>, property) {
+        Some(report) => report.to_qc_result(),
+        None => qc.quicktest(property),
+    }
+}
+
+/// `QuickCheck::quicksample`, seeded when `options` asks for it: the time to
+/// generate each input, with the input.
+pub fn quicksample<A>(
+    qc: &mut quickcheck::QuickCheck,
+    options: &Options,
+    property: fn(A) -> Option<bool>,
+) -> Vec<(Duration, String)>
+where
+    A: quickcheck::Arbitrary + Debug,
+    fn(A) -> Option<bool>: quickcheck::Testable,
+{
+    let sample = |seed, index| {
+        let start = Instant::now();
+        let input = replay(seed, index, quickcheck_input::<A>);
+        (start.elapsed(), format!("{:?}", input))
+    };
+    match options.mode {
+        Mode::Unseeded => qc.quicksample(property),
+        Mode::Seeded(seed) => (0..options.tests).map(|index| sample(seed, index)).collect(),
+        Mode::Replay { seed, index } => vec![sample(seed, index)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let (options, rest) = Options::parse(&args("--seed 7 --mutant insert_1"), 200).unwrap();
+        assert_eq!(options, Options { mode: Mode::Seeded(7), tests: 200 });
+        assert_eq!(rest, args("--mutant insert_1"));
+
+        let (options, _) = Options::parse(&args("--replay 7 --index 3 --tests 5"), 200).unwrap();
+        assert_eq!(options, Options { mode: Mode::Replay { seed: 7, index: 3 }, tests: 5 });
+        assert!(Options::parse(&args("--replay 7"), 200).is_err());
+        assert!(Options::parse(&args("--seed x"), 200).is_err());
+    }
+
+    #[test]
+    fn test_replay_matches_run() {
+        let generate = |rng: &mut StdRng, size: usize| rng.random_range(0..size * 1000);
+        let report = check(42, 1000, generate, |x| Some(x % 97 != 0));
+        let failure = report.failure.expect("some input is a multiple of 97");
+        let input = replay(42, failure.index, generate);
+        assert_eq!(format!("{:?}", input), failure.input);
+        assert_eq!(report.passed as usize, failure.index);
+    }
+
+    #[test]
+    fn test_outcomes_go_past_failures() {
+        let generate = |rng: &mut StdRng, size: usize| rng.random_range(0..size * 1000);
+        let property = |x: usize| {
+            assert!(x % 5 != 1);
+            (x % 7 != 0).then_some(x % 3 != 0)
+        };
+        let mut outcomes = vec![];
+        super::outcomes(42, 300, generate, property, |outcome| outcomes.push(outcome));
+        assert_eq!(outcomes.len(), 300);
+        for (index, outcome) in outcomes.into_iter().enumerate() {
+            let x = replay(42, index, generate);
+            let expected = if x % 5 == 1 { Some(false) } else { (x % 7 != 0).then_some(x % 3 != 0) };
+            assert_eq!(outcome, expected);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! `serialized`: checks a property against the inputs of an s-expression test
+//! file, or of the s-expression given in its place.
+
+use {
+    crate::{
+        Input,
+        Property,
+        Runner,
+        Workload,
+    },
+    etna_rs_utils::{
+        SamplingResult,
+        Status,
+    },
+    std::{
+        path::Path,
+        process::ExitCode,
+    },
+};
+
+struct Serialized<'a> {
+    tests: &'a str,
+}
+
+impl Runner for Serialized<'_> {
+    type Output = SamplingResult;
+
+    fn run<A: Input>(self, property: Property<A>) -> SamplingResult {
+        let mut discarded = 0;
+        let mut passed = 0;
+
+        let Ok(tests) = (property.parse)(self.tests) else {
+            return SamplingResult {
+                property: property.name.to_string(),
+                tests: 0,
+                status: Status::Aborted("failed to parse tests".to_string()),
+                passed,
+                discarded,
+            };
+        };
+
+        for input in tests.into_iter() {
+            match (property.function)(input.clone()) {
+                None => discarded += 1,
+                Some(true) => passed += 1,
+                Some(false) => {
+                    return SamplingResult {
+                        property: property.name.to_string(),
+                        tests: passed + discarded + 1,
+                        status: Status::FoundBug((property.show)(&input)),
+                        passed,
+                        discarded,
+                    };
+                },
+            }
+        }
+
+        SamplingResult {
+            property: property.name.to_string(),
+            tests: passed + discarded,
+            status: Status::Finished,
+            passed,
+            discarded,
+        }
+    }
+}
+
+pub fn sample<W: Workload>(property: &str, tests: &str) -> SamplingResult {
+    W::dispatch(property, Serialized { tests }).unwrap_or_else(|| SamplingResult {
+        property: property.to_string(),
+        tests: 0,
+        status: Status::Aborted(format!("Unknown property: {}", property)),
+        passed: 0,
+        discarded: 0,
+    })
+}
+
+pub fn main<W: Workload>() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    if crate::listing(&args) {
+        crate::list::<W>();
+        return ExitCode::SUCCESS;
+    }
+    if args.len() < 3 {
+        eprintln!("Usage: {} <tests> <property>", args[0]);
+        eprintln!("Tests should be an s-expression that is a list of test cases.");
+        eprintln!("For available properties, run {} --list-properties", args[0]);
+        return ExitCode::FAILURE;
+    }
+    let tests = args[1].as_str();
+    let property = args[2].as_str();
+
+    let tests = if Path::new(tests).exists() {
+        std::fs::read_to_string(tests).expect("Failed to read tests file")
+    } else {
+        tests.to_string()
+    };
+
+    let result = sample::<W>(property, &tests);
+
+    println!("{}", result);
+
+    match result.status {
+        Status::Finished => ExitCode::SUCCESS,
+        Status::FoundBug(_) | Status::Aborted(_) => ExitCode::FAILURE,
+    }
+}
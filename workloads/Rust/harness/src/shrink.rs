@@ -0,0 +1,137 @@
+//! Counterexample shrinking.
+//!
+//! crabcheck reports the first failing input it finds, which for the bespoke
+//! generators is often a large tree or term. Its coverage mostly reflects the
+//! size of the input rather than the fault, so `faultloc --record` can shrink
+//! the failure first and record the coverage of the minimized input instead
+//! of, or next to, the original one.
+
+use std::fmt::Debug;
+
+/// Inputs that can be made smaller while staying valid.
+pub trait Shrink: Sized {
+    /// Candidates strictly simpler than `self`, simplest first. Each must keep
+    /// whatever invariant the generator of the type guarantees, so that a
+    /// failure of the shrunk input is still a failure of the property and not
+    /// of its precondition.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+impl Shrink for i32 {
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = vec![0, self / 2, self - self.signum()];
+        candidates.dedup();
+        candidates.retain(|x| x != self);
+        candidates
+    }
+}
+
+impl Shrink for usize {
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = vec![0, self / 2, self.saturating_sub(1)];
+        candidates.dedup();
+        candidates.retain(|x| x != self);
+        candidates
+    }
+}
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self { vec![false] } else { vec![] }
+    }
+}
+
+macro_rules! shrink_tuple {
+    ($($ty:ident $index:tt),+) => {
+        impl<$($ty: Shrink + Clone),+> Shrink for ($($ty,)+) {
+            fn shrink(&self) -> Vec<Self> {
+                let mut candidates = vec![];
+                $(
+                    for candidate in self.$index.shrink() {
+                        let mut shrunk = self.clone();
+                        shrunk.$index = candidate;
+                        candidates.push(shrunk);
+                    }
+                )+
+                candidates
+            }
+        }
+    };
+}
+
+shrink_tuple!(A 0, B 1);
+shrink_tuple!(A 0, B 1, C 2);
+shrink_tuple!(A 0, B 1, C 2, D 3);
+shrink_tuple!(A 0, B 1, C 2, D 3, E 4);
+
+/// The result of [`minimize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shrunk<A> {
+    pub input: A,
+    /// How many candidates were taken on the way to `input`.
+    pub steps: usize,
+    /// How many times the property was evaluated.
+    pub evaluations: usize,
+}
+
+/// Greedily shrinks a failing `input`: takes the first candidate the property
+/// still fails on, until none does or `budget` evaluations are spent.
+pub fn minimize<A, F>(input: A, property: F, budget: usize) -> Shrunk<A>
+where
+    A: Shrink + Clone + Debug,
+    F: Fn(A) -> Option<bool>,
+{
+    let mut shrunk = Shrunk { input, steps: 0, evaluations: 0 };
+    'shrink: loop {
+        for candidate in shrunk.input.shrink() {
+            if shrunk.evaluations == budget {
+                break 'shrink;
+            }
+            shrunk.evaluations += 1;
+            if property(candidate.clone()) == Some(false) {
+                shrunk.input = candidate;
+                shrunk.steps += 1;
+                continue 'shrink;
+            }
+        }
+        break;
+    }
+    shrunk
+}
+
+/// Whose coverage a failing run records.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Record {
+    /// The failing input as found, without shrinking.
+    Original,
+    /// The minimized input only.
+    Minimized,
+    /// Both the failing input as found and the minimized one.
+    Both,
+}
+
+impl Record {
+    pub fn parse(value: &str) -> Result<Record, String> {
+        match value {
+            "original" => Ok(Record::Original),
+            "minimized" => Ok(Record::Minimized),
+            "both" => Ok(Record::Both),
+            _ => Err(format!("Invalid value for --record: {} (original, minimized, both)", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimize() {
+        let shrunk = minimize((1234, -567), |(x, y): (i32, i32)| Some(x < 100 || y > -3), 10_000);
+        assert_eq!(shrunk.input, (100, -3));
+
+        let shrunk = minimize((1234, -567), |(x, _): (i32, i32)| Some(x < 100), 5);
+        assert_eq!(shrunk.evaluations, 5);
+        assert!(shrunk.input.0 >= 100);
+    }
+}
@@ -0,0 +1,45 @@
+//! Input sizes.
+//!
+//! Recursive functions run more often the bigger their input is, so raw hit
+//! counts grow with the input rather than with the fault. Every snapshot is
+//! recorded along with the [`Size`] of the input it was taken around, so that
+//! `faultloc-analysis --model size` can normalize the counts by it.
+
+/// Inputs with a natural size: the nodes of a tree, the size of a term.
+pub trait Size {
+    fn size(&self) -> usize;
+}
+
+// Keys and values are not what recursion runs over.
+impl Size for i32 {
+    fn size(&self) -> usize {
+        0
+    }
+}
+
+impl Size for usize {
+    fn size(&self) -> usize {
+        0
+    }
+}
+
+impl Size for bool {
+    fn size(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! size_tuple {
+    ($($ty:ident $index:tt),+) => {
+        impl<$($ty: Size),+> Size for ($($ty,)+) {
+            fn size(&self) -> usize {
+                0 $(+ self.$index.size())+
+            }
+        }
+    };
+}
+
+size_tuple!(A 0, B 1);
+size_tuple!(A 0, B 1, C 2);
+size_tuple!(A 0, B 1, C 2, D 3);
+size_tuple!(A 0, B 1, C 2, D 3, E 4);
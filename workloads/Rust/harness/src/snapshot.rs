@@ -0,0 +1,167 @@
+//! Per-test coverage snapshots through the compiler-rt profile runtime.
+//!
+//! When the binary is built with `-C instrument-coverage`, [`per_test`] resets
+//! the profile counters before every property evaluation and dumps them right
+//! after it to `coverage/snapshot_<pid>-<index>-<outcome>.profraw`, where the
+//! outcome is `pass`, `fail` or `discard`. [`contrast`] does the same for the
+//! failing inputs and passing neighbors of a contrastive run. The size of the
+//! input of each snapshot goes to `coverage/sizes_<pid>.txt`, one
+//! `<snapshot> <size>` line per snapshot. Without instrumentation the runtime
+//! symbols resolve to null and evaluations run unobserved.
+
+use std::{
+    ffi::{CString, c_char, c_int},
+    io::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::size::Size;
+
+unsafe extern "C" {
+    #[linkage = "extern_weak"]
+    static __llvm_profile_reset_counters: Option<unsafe extern "C" fn()>;
+    #[linkage = "extern_weak"]
+    static __llvm_profile_set_filename: Option<unsafe extern "C" fn(*const c_char)>;
+    #[linkage = "extern_weak"]
+    static __llvm_profile_write_file: Option<unsafe extern "C" fn() -> c_int>;
+}
+
+pub(crate) const SNAPSHOT_DIR: &str = "coverage";
+
+// Counters accumulated outside of property evaluations (generation, mutation,
+// bookkeeping) are flushed here at exit, away from the `snapshot_*` files.
+const RESIDUAL_FILE: &str = "coverage/residual_%p.profraw";
+
+static INDEX: AtomicUsize = AtomicUsize::new(0);
+
+pub fn is_instrumented() -> bool {
+    unsafe {
+        __llvm_profile_reset_counters.is_some()
+            && __llvm_profile_set_filename.is_some()
+            && __llvm_profile_write_file.is_some()
+    }
+}
+
+fn reset_counters() {
+    if let Some(reset) = unsafe { __llvm_profile_reset_counters } {
+        unsafe { reset() };
+    }
+}
+
+fn set_filename(path: &str) {
+    let Some(set_filename) = (unsafe { __llvm_profile_set_filename }) else { return };
+    let path = CString::new(path).expect("profile file names never contain NUL bytes");
+    // The runtime copies the pattern, so the name only has to outlive the call.
+    unsafe { set_filename(path.as_ptr()) };
+}
+
+fn write_file() -> bool {
+    match unsafe { __llvm_profile_write_file } {
+        Some(write_file) => unsafe { write_file() == 0 },
+        None => false,
+    }
+}
+
+fn outcome(result: Option<bool>) -> &'static str {
+    match result {
+        Some(true) => "pass",
+        Some(false) => "fail",
+        None => "discard",
+    }
+}
+
+/// Number of snapshots written so far.
+pub fn count() -> usize {
+    INDEX.load(Ordering::SeqCst)
+}
+
+/// Wraps a property so that each evaluation leaves its own tagged snapshot.
+pub fn per_test<A, F>(property: F) -> impl Fn(A) -> Option<bool>
+where
+    A: Size,
+    F: Fn(A) -> Option<bool>,
+{
+    per_test_if(property, |_| true)
+}
+
+/// [`per_test`], leaving a snapshot only for the evaluations whose result
+/// `keep` accepts. The others are dropped without using up an index.
+pub fn per_test_if<A, F, K>(property: F, keep: K) -> impl Fn(A) -> Option<bool>
+where
+    A: Size,
+    F: Fn(A) -> Option<bool>,
+    K: Fn(Option<bool>) -> bool,
+{
+    move |input| {
+        if !is_instrumented() {
+            return property(input);
+        }
+
+        let size = input.size();
+        reset_counters();
+        let result = property(input);
+        if !keep(result) {
+            reset_counters();
+            return result;
+        }
+
+        let index = INDEX.fetch_add(1, Ordering::SeqCst);
+        dump(&format!("snapshot_{}-{}-{}", std::process::id(), index, outcome(result)), size);
+
+        result
+    }
+}
+
+/// Evaluates `input` as member `member` of the contrastive group `group`,
+/// leaving `contrast_<pid>-<group>-<member>-<outcome>.profraw` behind when
+/// `keep` accepts the result. Member 0 is the failing input the group was
+/// built around, the others are passing neighbors of it.
+pub fn contrast<A, F, K>(
+    property: F,
+    group: usize,
+    member: usize,
+    input: A,
+    keep: K,
+) -> Option<bool>
+where
+    A: Size,
+    F: Fn(A) -> Option<bool>,
+    K: Fn(Option<bool>) -> bool,
+{
+    if !is_instrumented() {
+        return property(input);
+    }
+
+    let size = input.size();
+    reset_counters();
+    let result = property(input);
+    if keep(result) {
+        let name =
+            format!("contrast_{}-{}-{}-{}", std::process::id(), group, member, outcome(result));
+        dump(&name, size);
+    } else {
+        reset_counters();
+    }
+    result
+}
+
+// Writes the counters accumulated since the last reset to `name`, notes the
+// size of its input and goes back to collecting into the residual file.
+fn dump(name: &str, size: usize) {
+    set_filename(&format!("{}/{}.profraw", SNAPSHOT_DIR, name));
+    if !write_file() {
+        tracing::warn!("failed to write coverage snapshot {}", name);
+    }
+    set_filename(RESIDUAL_FILE);
+    reset_counters();
+
+    let sizes = format!("{}/sizes_{}.txt", SNAPSHOT_DIR, std::process::id());
+    let written = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&sizes)
+        .and_then(|mut file| writeln!(file, "{} {}", name, size));
+    if let Err(e) = written {
+        tracing::warn!("failed to record the input size of {}: {}", name, e);
+    }
+}
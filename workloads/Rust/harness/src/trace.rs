@@ -0,0 +1,240 @@
+//! Execution traces through `tracing` spans.
+//!
+//! Workloads built with their `traced` feature open a span for every call of
+//! their implementation functions, recording its arguments and return value.
+//! Under `faultloc --trace`, [`Recorder`] keeps the calls of each evaluation
+//! as a call tree, and [`per_test_if`] writes it to
+//! `traces/trace_<pid>-<index>-<outcome>.json` along with how often each
+//! function was entered and exited, for `faultloc-analysis sequences`. Unlike
+//! coverage snapshots, traces need no `-C instrument-coverage` build.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt::{Debug, Write},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use serde_json::json;
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id},
+};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+
+const TRACE_DIR: &str = "traces";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// One call of a traced function. Calls are kept in the order they were
+/// entered, so that the tree is their `depth`s in preorder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub function: &'static str,
+    pub depth: usize,
+    pub args: String,
+    /// The return value, once the call returned.
+    pub ret: Option<String>,
+}
+
+/// The calls of one evaluation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub calls: Vec<Call>,
+    /// How often each function was entered and exited.
+    pub counts: BTreeMap<&'static str, (usize, usize)>,
+    /// The indices in `calls` of the calls still running.
+    open: Vec<usize>,
+}
+
+impl Trace {
+    pub fn to_json(&self) -> serde_json::Value {
+        let calls = self
+            .calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "function": call.function,
+                    "depth": call.depth,
+                    "args": call.args,
+                    "ret": call.ret,
+                })
+            })
+            .collect::<Vec<_>>();
+        let counts = self
+            .counts
+            .iter()
+            .map(|(function, (enter, exit))| (*function, json!({ "enter": enter, "exit": exit })))
+            .collect::<BTreeMap<_, _>>();
+        json!({ "calls": calls, "counts": counts })
+    }
+}
+
+thread_local! {
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+    static TRACE: RefCell<Trace> = RefCell::new(Trace::default());
+}
+
+fn record(f: impl FnOnce(&mut Trace)) {
+    if RECORDING.with(Cell::get) {
+        TRACE.with(|trace| f(&mut trace.borrow_mut()));
+    }
+}
+
+/// The fields of a span or event, as `name: value` pairs.
+#[derive(Default)]
+struct Fields {
+    args: String,
+    ret: Option<String>,
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "return" {
+            self.ret = Some(format!("{:?}", value));
+            return;
+        }
+        if !self.args.is_empty() {
+            self.args.push_str(", ");
+        }
+        let _ = write!(self.args, "{}: {:?}", field.name(), value);
+    }
+}
+
+/// The layer that records the calls of traced evaluations.
+pub struct Recorder;
+
+impl Recorder {
+    /// The layer to install, switching [`per_test_if`] on.
+    pub fn enable() -> Recorder {
+        ENABLED.store(true, Ordering::SeqCst);
+        Recorder
+    }
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let function = span.metadata().name();
+        let args = span.extensions().get::<Fields>().map(|f| f.args.clone()).unwrap_or_default();
+        record(|trace| {
+            trace.counts.entry(function).or_default().0 += 1;
+            trace.open.push(trace.calls.len());
+            let depth = trace.open.len() - 1;
+            trace.calls.push(Call { function, depth, args, ret: None });
+        });
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let function = span.metadata().name();
+        record(|trace| {
+            trace.counts.entry(function).or_default().1 += 1;
+            trace.open.pop();
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let Some(ret) = fields.ret else { return };
+        record(|trace| {
+            if let Some(&open) = trace.open.last() {
+                trace.calls[open].ret = Some(ret);
+            }
+        });
+    }
+}
+
+/// Whether a [`Recorder`] is installed.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Number of traces written so far.
+pub fn count() -> usize {
+    INDEX.load(Ordering::SeqCst)
+}
+
+/// Records the calls of `property` alone.
+fn traced<A, F>(property: F, input: A) -> (Option<bool>, Trace)
+where
+    F: Fn(A) -> Option<bool>,
+{
+    TRACE.with(|trace| trace.take());
+    RECORDING.with(|recording| recording.set(true));
+    let result = property(input);
+    RECORDING.with(|recording| recording.set(false));
+    (result, TRACE.with(|trace| trace.take()))
+}
+
+/// Wraps a property so that each evaluation whose result `keep` accepts
+/// leaves its trace behind, when a [`Recorder`] is installed.
+pub fn per_test_if<A, F, K>(property: F, keep: K) -> impl Fn(A) -> Option<bool>
+where
+    F: Fn(A) -> Option<bool>,
+    K: Fn(Option<bool>) -> bool,
+{
+    move |input| {
+        if !is_enabled() {
+            return property(input);
+        }
+
+        let (result, trace) = traced(&property, input);
+        if keep(result) {
+            let outcome = match result {
+                Some(true) => "pass",
+                Some(false) => "fail",
+                None => "discard",
+            };
+            let index = INDEX.fetch_add(1, Ordering::SeqCst);
+            let name = format!("trace_{}-{}-{}", std::process::id(), index, outcome);
+            let path = format!("{}/{}.json", TRACE_DIR, name);
+            let written = std::fs::create_dir_all(TRACE_DIR)
+                .and_then(|()| std::fs::write(&path, trace.to_json().to_string()));
+            if let Err(e) = written {
+                tracing::warn!("failed to write trace {}: {}", name, e);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[tracing::instrument(level = "trace", ret)]
+    fn fib(n: u32) -> u32 {
+        if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+    }
+
+    #[test]
+    fn test_recorder() {
+        let subscriber = tracing_subscriber::registry().with(Recorder::enable());
+        let (result, trace) = tracing::subscriber::with_default(subscriber, || {
+            fib(1);
+            traced(|n| Some(fib(n) == 1), 2)
+        });
+        assert_eq!(result, Some(true));
+        let calls = trace.calls.iter().map(|c| (c.depth, c.args.as_str())).collect::<Vec<_>>();
+        assert_eq!(calls, vec![(0, "n: 2"), (1, "n: 1"), (1, "n: 0")]);
+        assert_eq!(trace.calls[0].ret.as_deref(), Some("1"));
+        assert_eq!(trace.counts["fib"], (3, 3));
+    }
+}
@@ -0,0 +1,95 @@
+//! Structured oracle verdicts.
+//!
+//! A validity property only says whether its data structure was valid. A spec
+//! that can tell which of its invariants broke, and where, hands a [`Verdict`]
+//! to [`keep`], and `faultloc` reports the verdict of each recorded failure,
+//! so that failures can be told apart by what they broke.
+
+use std::{cell::RefCell, fmt::Display};
+
+/// One invariant a value broke.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Broken {
+    pub invariant: &'static str,
+    /// Where in the value it first broke, as `L` and `R` steps from the root
+    /// of a tree; empty for the root itself.
+    pub path: String,
+}
+
+/// Every invariant a value broke, in the order the spec checks them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verdict {
+    pub broken: Vec<Broken>,
+}
+
+impl Verdict {
+    pub fn holds(&self) -> bool {
+        self.broken.is_empty()
+    }
+
+    /// Notes that `invariant` broke at `path`, if it did.
+    pub fn check(&mut self, invariant: &'static str, path: Option<String>) {
+        if let Some(path) = path {
+            self.broken.push(Broken { invariant, path });
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.holds() {
+            return write!(f, "holds");
+        }
+        for (i, broken) in self.broken.iter().enumerate() {
+            let path = if broken.path.is_empty() { "root" } else { &broken.path };
+            write!(f, "{}{} at {}", if i == 0 { "" } else { ", " }, broken.invariant, path)?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    static FIRST: RefCell<Option<Verdict>> = const { RefCell::new(None) };
+}
+
+/// Keeps `verdict` if it is the first broken one since the last [`take`], and
+/// returns whether it holds.
+pub fn keep(verdict: Verdict) -> bool {
+    let holds = verdict.holds();
+    if !holds {
+        FIRST.with(|first| {
+            first.borrow_mut().get_or_insert(verdict);
+        });
+    }
+    holds
+}
+
+/// Starts a new evaluation.
+pub fn reset() {
+    take();
+}
+
+/// The first broken verdict since the last [`reset`] or call, if any.
+pub fn take() -> Option<Verdict> {
+    FIRST.with(|first| first.borrow_mut().take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_first_broken() {
+        let mut broken = Verdict::default();
+        broken.check("no_red_red", Some(String::new()));
+        broken.check("bst", None);
+        broken.check("black_height", Some("LR".to_string()));
+        assert_eq!(broken.to_string(), "no_red_red at root, black_height at LR");
+
+        assert!(keep(Verdict::default()));
+        assert!(!keep(broken.clone()));
+        assert!(!keep(Verdict { broken: broken.broken[1..].to_vec() }));
+        assert_eq!(take(), Some(broken));
+        assert_eq!(take(), None);
+    }
+}